@@ -4,7 +4,7 @@ extern crate webview_rs;
 use std::thread;
 use std::time;
 
-use webview_rs::{Arg, Builder, Content};
+use webview_rs::{Arg, Builder, HtmlDocument};
 
 const THREADS: usize = 16;
 
@@ -12,16 +12,18 @@ const HTML: &'static str = include_str!("../assets/index.html");
 const CSS: &'static str = include_str!("../assets/styles.css");
 
 fn main() {
+    // the document is self-contained, since embedded assets are only
+    // supported by the GTK backend
+    let document = HtmlDocument::new(HTML).add_style(CSS);
+
     let mut webview = Builder::without_userdata()
         .set_title("Stresstest")
-        .set_content(Content::Html(HTML))
+        .set_content(document)
         .set_size(800, 600)
         .set_debug(true)
         .build()
         .unwrap();
 
-    webview.inject_css(CSS).expect("could not inject css");
-
     for id in 0..THREADS {
         let handle = webview.thread_handle();
         thread::spawn(move || loop {
@@ -36,9 +38,9 @@ fn main() {
                 _ => unreachable!(),
             };
 
-            let result = handle.try_dispatch(|webview, _| {
+            let result = handle.try_dispatch(move |webview, _| {
                 webview
-                    .eval_fn("setColor", &[Arg::Int(id), Arg::Str(color)])
+                    .eval_fn("setColor", &[Arg::from(id), Arg::from(color)])
                     .unwrap();
             });
 
@@ -4,7 +4,7 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time;
 
-use webview_rs::{Arg, Builder, Content, Webview};
+use webview_rs::{Builder, Content, Router, Webview};
 
 static HTML_DATA: &'static str = include_str!("../index.html");
 
@@ -32,18 +32,9 @@ impl Timer {
 
 type Userdata = Arc<Timer>;
 
-//Could use a closure instead
-fn external_invoke(webview: &mut Webview, userdata: &mut Userdata, arg: &str) {
-    match arg {
-        "reset" => reset_invoke(webview, userdata),
-        "exit" => exit_invoke(webview),
-        _ => {}
-    }
-}
-
 fn reset_invoke(webview: &mut Webview, userdata: &mut Userdata) {
     userdata.set(0);
-    webview.eval_fn("updateTicks", &[Arg::Int(0)]).unwrap();
+    webview.emit("ticks", 0).unwrap();
 }
 
 fn exit_invoke(webview: &mut Webview) {
@@ -56,7 +47,11 @@ fn main() {
         .set_title("Timer")
         .set_content(Content::Html(HTML_DATA))
         .set_size(400, 400)
-        .set_external_invoke(external_invoke)
+        .set_external_invoke(
+            Router::new()
+                .route("reset", |webview, userdata, _| reset_invoke(webview, userdata))
+                .route("exit", |webview, _, _| exit_invoke(webview)),
+        )
         //.set_external_invoke(|webview, userdata, arg| { ... })
         .build()
         .expect("could not create webview");
@@ -66,10 +61,7 @@ fn main() {
     thread::spawn(move || loop {
         thread::sleep(time::Duration::from_millis(100));
         timer.incr();
-        let result = thread_handle.try_dispatch(|webview, userdata| {
-            let ticks = userdata.get();
-            webview.eval_fn("updateTicks", &[Arg::Int(ticks)]).unwrap();
-        });
+        let result = thread_handle.emit("ticks", timer.get());
 
         if result.is_err() {
             break;
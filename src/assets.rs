@@ -0,0 +1,399 @@
+//! Module for bundles of frontend assets embedded into the binary.
+//!
+//! An `Assets` bundle maps paths to file contents and is served to the webview
+//! through the internal custom URI scheme `webview-rs` when passed to the
+//! `Builder` as `Content::Assets`. The MIME type of each asset is derived from
+//! its file extension.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The scheme used for serving the `Assets` of a webview
+pub const ASSETS_SCHEME: &str = "webview-rs";
+/// The URL at which the `Assets` of a webview are served
+pub const ASSETS_URL: &str = "webview-rs://assets/";
+
+/// Embeds files from a directory (relative to the crate root) as `Assets`
+///
+/// Since the contents of a directory can not be listed by a macro, the
+/// individual files have to be stated explicitly. The files are stored under
+/// their paths relative to the directory. Use `generate_assets` in a build
+/// script to embed a whole directory.
+///
+/// # Examples
+///
+/// ```ignore
+/// let assets = include_assets!("frontend", "index.html", "css/app.css", "js/app.js");
+/// ```
+#[macro_export]
+macro_rules! include_assets {
+    ($dir:expr, $($file:expr),+ $(,)*) => {
+        $crate::Assets::new()
+            $(.add(
+                $file,
+                &include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $dir, "/", $file))[..],
+            ))+
+    };
+}
+
+/// Generates a Rust expression embedding all files of a directory as
+/// `Assets`, to be called from a build script
+///
+/// The expression is written to `out_file` and can be included with
+/// `include!`. The files are stored under their paths relative to the
+/// directory and Cargo is instructed to re-run the build script whenever the
+/// contents of the directory change.
+///
+/// # Errors
+///
+/// Fails if the directory can not be read, contains paths which are not valid
+/// UTF-8 or the output file can not be written.
+///
+/// # Examples
+///
+/// In `build.rs`:
+///
+/// ```ignore
+/// fn main() {
+///     let out_dir = std::env::var("OUT_DIR").unwrap();
+///     let out_file = std::path::Path::new(&out_dir).join("assets.rs");
+///     webview_rs::generate_assets("frontend", out_file).unwrap();
+/// }
+/// ```
+///
+/// In the crate:
+///
+/// ```ignore
+/// let assets = include!(concat!(env!("OUT_DIR"), "/assets.rs"));
+/// ```
+#[inline]
+pub fn generate_assets(dir: impl AsRef<Path>, out_file: impl AsRef<Path>) -> io::Result<()> {
+    let dir = dir.as_ref().canonicalize()?;
+    let expr = assets_expr(&dir)?;
+    fs::write(out_file, expr)?;
+
+    println!("cargo:rerun-if-changed={}", utf8(&dir)?);
+    Ok(())
+}
+
+/// Generates the `Assets` expression for all files in a directory, sorted by
+/// their relative paths.
+fn assets_expr(dir: &Path) -> io::Result<String> {
+    let mut files = Vec::new();
+    collect_files(dir, "", &mut files)?;
+    files.sort();
+
+    let mut expr = String::from("::webview_rs::Assets::new()");
+    for path in files {
+        let file = utf8(&dir.join(&path))?.to_owned();
+        write!(
+            expr,
+            "\n    .add({:?}, &include_bytes!({:?})[..])",
+            path, file
+        )
+        .unwrap();
+    }
+
+    Ok(expr)
+}
+
+/// Recursively collects the relative paths of all files in a directory,
+/// separated by `/` on all platforms.
+fn collect_files(dir: &Path, prefix: &str, files: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| invalid_path())?;
+        let path = format!("{}{}", prefix, name);
+
+        if entry.path().is_dir() {
+            collect_files(&entry.path(), &format!("{}/", path), files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[inline]
+fn utf8(path: &Path) -> io::Result<&str> {
+    path.to_str().ok_or_else(invalid_path)
+}
+
+#[inline]
+fn invalid_path() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "path is not valid UTF-8")
+}
+
+/// A single embedded file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Asset {
+    data:      Cow<'static, [u8]>,
+    mime_type: Cow<'static, str>,
+}
+
+impl Asset {
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    #[inline]
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+}
+
+/// A bundle of embedded frontend files
+///
+/// Requests for directories (including the root) are answered with the index
+/// file of the directory. If the fallback is enabled, requests for paths
+/// without a file extension that do not exist are answered with the root
+/// index file, so client side routing of single page applications works.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assets {
+    files:    HashMap<Cow<'static, str>, Asset>,
+    index:    Cow<'static, str>,
+    fallback: bool,
+}
+
+impl Assets {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            files:    HashMap::new(),
+            index:    Cow::Borrowed("index.html"),
+            fallback: false,
+        }
+    }
+
+    /// Adds a file, the MIME type is derived from the file extension.
+    #[inline]
+    pub fn add(
+        self,
+        path: impl Into<Cow<'static, str>>,
+        data: impl Into<Cow<'static, [u8]>>,
+    ) -> Self {
+        let path = path.into();
+        let mime_type = mime_type(&path);
+        self.add_with_mime_type(path, data, mime_type)
+    }
+
+    /// Adds a file with an explicit MIME type.
+    #[inline]
+    pub fn add_with_mime_type(
+        mut self,
+        path: impl Into<Cow<'static, str>>,
+        data: impl Into<Cow<'static, [u8]>>,
+        mime_type: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        let path = match path.into() {
+            Cow::Borrowed(path) => Cow::Borrowed(path.trim_start_matches('/')),
+            Cow::Owned(path) => Cow::Owned(path.trim_start_matches('/').to_owned()),
+        };
+
+        let asset = Asset {
+            data:      data.into(),
+            mime_type: mime_type.into(),
+        };
+
+        self.files.insert(path, asset);
+        self
+    }
+
+    /// Sets the name of the index files (`index.html` by default).
+    #[inline]
+    pub fn set_index(mut self, index: impl Into<Cow<'static, str>>) -> Self {
+        self.index = index.into();
+        self
+    }
+
+    /// Enables or disables the fallback to the root index file for single
+    /// page applications (disabled by default).
+    #[inline]
+    pub fn set_fallback(mut self, fallback: bool) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Resolves a requested URL path to the asset to be served.
+    ///
+    /// Query strings and fragments must already have been removed from the
+    /// path.
+    #[inline]
+    pub fn resolve(&self, path: &str) -> Option<&Asset> {
+        let path = path.trim_start_matches('/');
+
+        if path.is_empty() || path.ends_with('/') {
+            let index = format!("{}{}", path, self.index);
+            return self
+                .files
+                .get(index.as_str())
+                .or_else(|| self.root_fallback(path));
+        }
+
+        self.files.get(path).or_else(|| {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            if name.contains('.') {
+                None
+            } else {
+                self.root_fallback(path)
+            }
+        })
+    }
+
+    #[inline]
+    fn root_fallback(&self, path: &str) -> Option<&Asset> {
+        if self.fallback || path.is_empty() {
+            self.files.get(self.index.as_ref())
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Assets {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the MIME type for a file path based on its extension.
+///
+/// Unknown extensions are mapped to `application/octet-stream`.
+#[inline]
+pub fn mime_type(path: &str) -> &'static str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let extension = match name.rfind('.') {
+        Some(idx) => name[idx + 1..].to_ascii_lowercase(),
+        None => return "application/octet-stream",
+    };
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json",
+        "txt" => "text/plain; charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "bmp" => "image/bmp",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::assets::*;
+
+    #[test]
+    fn mime_types() {
+        assert_eq!(mime_type("index.html"), "text/html; charset=utf-8");
+        assert_eq!(mime_type("js/app.min.JS"), "text/javascript; charset=utf-8");
+        assert_eq!(mime_type("fonts/icons.woff2"), "font/woff2");
+        assert_eq!(mime_type("dir.d/LICENSE"), "application/octet-stream");
+        assert_eq!(mime_type("archive.xyz"), "application/octet-stream");
+    }
+
+    #[test]
+    fn resolve() {
+        let assets = Assets::new()
+            .add("/index.html", &b"root"[..])
+            .add("css/app.css", &b"css"[..])
+            .add("docs/index.html", &b"docs"[..])
+            .add_with_mime_type("data", &b"data"[..], "text/plain");
+
+        assert_eq!(assets.len(), 4);
+        assert_eq!(assets.resolve("/").unwrap().data(), b"root");
+        assert_eq!(assets.resolve("").unwrap().data(), b"root");
+        assert_eq!(assets.resolve("/index.html").unwrap().data(), b"root");
+        assert_eq!(assets.resolve("/docs/").unwrap().data(), b"docs");
+        assert_eq!(assets.resolve("/data").unwrap().mime_type(), "text/plain");
+
+        let css = assets.resolve("/css/app.css").unwrap();
+        assert_eq!(css.mime_type(), "text/css; charset=utf-8");
+
+        assert!(assets.resolve("/missing.css").is_none());
+        assert!(assets.resolve("/users/42").is_none());
+        assert!(assets.resolve("/other/").is_none());
+    }
+
+    #[test]
+    fn generate() {
+        let dir = std::env::temp_dir().join(format!("webview_rs_assets_{}", std::process::id()));
+        fs::create_dir_all(dir.join("css/theme")).unwrap();
+        fs::write(dir.join("index.html"), "index").unwrap();
+        fs::write(dir.join("css/app.css"), "app").unwrap();
+        fs::write(dir.join("css/theme/dark \"1\".css"), "dark").unwrap();
+
+        let dir = dir.canonicalize().unwrap();
+        let out_file = dir.with_extension("rs");
+        generate_assets(&dir, &out_file).unwrap();
+        let expr = fs::read_to_string(&out_file).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_file(&out_file).unwrap();
+
+        let add = |path: &str| {
+            let file = dir.join(path);
+            format!(
+                "\n    .add({:?}, &include_bytes!({:?})[..])",
+                path,
+                file.to_str().unwrap()
+            )
+        };
+        let expected = format!(
+            "::webview_rs::Assets::new(){}{}{}",
+            add("css/app.css"),
+            add("css/theme/dark \"1\".css"),
+            add("index.html")
+        );
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn fallback() {
+        let assets = Assets::new()
+            .add("app.html", &b"app"[..])
+            .set_index("app.html")
+            .set_fallback(true);
+
+        assert_eq!(assets.resolve("/").unwrap().data(), b"app");
+        assert_eq!(assets.resolve("/users/42").unwrap().data(), b"app");
+        assert_eq!(assets.resolve("/users/").unwrap().data(), b"app");
+        assert!(assets.resolve("/users/avatar.png").is_none());
+    }
+}
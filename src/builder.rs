@@ -1,26 +1,61 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::mem;
-use std::path::PathBuf; //TODO: add set_content for Path
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::thread;
 
-use crate::content::Content;
+use crate::assets::{Assets, ASSETS_URL};
+use crate::callback::ExternalInvoke;
+use crate::content::{self, Content};
 use crate::conversion::convert_to_cstring;
+use crate::dispatch::DispatchQueue;
 use crate::error::WebviewError;
 use crate::eval::StringBuffers;
+use crate::event;
 use crate::ffi;
+use crate::lifecycle::Hooks;
+use crate::navigation::{NavigationFnBox, NavigationRequest, Policy};
+use crate::scheme::{self, SchemeFnBox, SchemeResponse};
+#[cfg(feature = "server")]
+use crate::server::AssetServer;
+use crate::timer::Timers;
+use crate::user_content::{UserScript, UserStyle};
+use crate::window::{InitialState, WindowState};
 use crate::{Extension, ExternalInvokeFnBox, Webview, WebviewHandle, WebviewWrapper};
 use webview_sys as sys;
 
+/// The content set for a webview, URLs are validated once the webview is
+/// built, since the default scheme may still change until then
+enum PendingContent<'content> {
+    Url(Cow<'content, str>),
+    Resolved(Result<Cow<'content, str>, WebviewError>),
+}
+
 pub struct Builder<'title, 'content, 'invoke, T> {
-    title:           Option<Cow<'title, str>>,
-    content:         Option<Cow<'content, str>>,
-    size:            Option<(usize, usize)>,
-    resizable:       bool,
-    debug:           bool,
-    external_invoke: Option<ExternalInvokeFnBox<'invoke, T>>,
-    userdata:        T,
-    thread_check:    bool,
-    buffer_size:     usize,
+    title:              Option<Cow<'title, str>>,
+    content:            Option<PendingContent<'content>>,
+    https_default:      bool,
+    assets:             Option<Assets>,
+    #[cfg(feature = "server")]
+    server:             Option<AssetServer>,
+    size:               Option<(usize, usize)>,
+    resizable:          bool,
+    decorations:        bool,
+    always_on_top:      bool,
+    transparent:        bool,
+    icon:               Option<Cow<'static, [u8]>>,
+    initial_state:      InitialState,
+    debug:              bool,
+    external_invoke:    Option<ExternalInvokeFnBox<'invoke, T>>,
+    navigation_handler: Option<NavigationFnBox<'invoke>>,
+    schemes:            HashMap<String, SchemeFnBox<'invoke>>,
+    hooks:              Hooks<'invoke, T>,
+    user_scripts:       Vec<UserScript>,
+    user_styles:        Vec<UserStyle>,
+    userdata:           T,
+    thread_check:       bool,
+    buffer_size:        usize,
 }
 
 impl<'title, 'content, 'invoke> Builder<'title, 'content, 'invoke, ()> {
@@ -28,15 +63,29 @@ impl<'title, 'content, 'invoke> Builder<'title, 'content, 'invoke, ()> {
     pub fn without_userdata() -> Self {
         sys::runtime_size_check();
         Builder {
-            title:           None,
-            content:         None,
-            size:            None,
-            resizable:       true,
-            debug:           false,
-            external_invoke: None,
-            userdata:        (),
-            thread_check:    true,
-            buffer_size:     0,
+            title:              None,
+            content:            None,
+            https_default:      false,
+            assets:             None,
+            #[cfg(feature = "server")]
+            server:             None,
+            size:               None,
+            resizable:          true,
+            decorations:        true,
+            always_on_top:      false,
+            transparent:        false,
+            icon:               None,
+            initial_state:      InitialState::Normal,
+            debug:              false,
+            external_invoke:    None,
+            navigation_handler: None,
+            schemes:            HashMap::new(),
+            hooks:              Hooks::new(),
+            user_scripts:       Vec::new(),
+            user_styles:        Vec::new(),
+            userdata:           (),
+            thread_check:       true,
+            buffer_size:        0,
         }
     }
 }
@@ -48,10 +97,24 @@ impl<'title, 'content, 'invoke, T> Builder<'title, 'content, 'invoke, T> {
         Builder {
             title: None,
             content: None,
+            https_default: false,
+            assets: None,
+            #[cfg(feature = "server")]
+            server: None,
             size: None,
             resizable: true,
+            decorations: true,
+            always_on_top: false,
+            transparent: false,
+            icon: None,
+            initial_state: InitialState::Normal,
             debug: false,
             external_invoke: None,
+            navigation_handler: None,
+            schemes: HashMap::new(),
+            hooks: Hooks::new(),
+            user_scripts: Vec::new(),
+            user_styles: Vec::new(),
             userdata,
             thread_check: true,
             buffer_size: 0,
@@ -74,12 +137,32 @@ impl<'title, 'content, 'invoke, T> Builder<'title, 'content, 'invoke, T> {
         self.set_content(Content::Html(html))
     }
 
+    /// Set a local file as content
+    ///
+    /// The path is canonicalized and converted into a `file://` URL, if the
+    /// file does not exist, `build` fails with `WebviewError::InvalidPath`.
     #[inline]
-    pub fn set_content_file(self, filepath: impl Into<PathBuf>) -> Self {
+    pub fn set_content_file(mut self, filepath: impl Into<PathBuf>) -> Self {
+        self.assets = None;
+        #[cfg(feature = "server")]
+        {
+            self.server = None;
+        }
+
         let filepath = filepath.into();
-        let string = filepath.to_string_lossy().into_owned();
+        self.content = Some(PendingContent::Resolved(
+            content::file_url(&filepath).map(Cow::Owned),
+        ));
+        self
+    }
 
-        self.set_content(Content::File(string))
+    /// Set a bundle of embedded assets as content
+    ///
+    /// The assets are served through an internal custom URI scheme, starting
+    /// with the index file. Only supported by the GTK backend.
+    #[inline]
+    pub fn set_content_assets(self, assets: Assets) -> Self {
+        self.set_content(Content::<&str>::Assets(assets))
     }
 
     #[inline]
@@ -87,7 +170,42 @@ impl<'title, 'content, 'invoke, T> Builder<'title, 'content, 'invoke, T> {
     where
         C: Into<Cow<'content, str>>,
     {
-        self.content = Some(content.into().into());
+        self.assets = None;
+        #[cfg(feature = "server")]
+        {
+            self.server = None;
+        }
+
+        match content.into() {
+            Content::Assets(assets) => {
+                self.assets = Some(assets);
+                self.content = Some(PendingContent::Resolved(Ok(Cow::Borrowed(ASSETS_URL))));
+            }
+            #[cfg(feature = "server")]
+            Content::Served(server) => {
+                self.content = Some(PendingContent::Resolved(Ok(Cow::Owned(server.url()))));
+                self.server = Some(server);
+            }
+            Content::File(path) => {
+                let path = path.into();
+                let url = content::file_url(Path::new(path.as_ref())).map(Cow::Owned);
+                self.content = Some(PendingContent::Resolved(url));
+            }
+            Content::Url(url) => self.content = Some(PendingContent::Url(url.into())),
+            content => self.content = Some(PendingContent::Resolved(Ok(content.into_url()))),
+        }
+
+        self
+    }
+
+    /// Set whether URLs without a scheme (e.g. `example.com`) default to
+    /// `https://` instead of `http://` (disabled by default)
+    ///
+    /// This applies to the initial content and to later calls of
+    /// `Webview::navigate`.
+    #[inline]
+    pub fn set_https_default(mut self, https_default: bool) -> Self {
+        self.https_default = https_default;
         self
     }
 
@@ -104,18 +222,180 @@ impl<'title, 'content, 'invoke, T> Builder<'title, 'content, 'invoke, T> {
         self
     }
 
+    /// Show or hide the window decorations, i.e. the title bar and borders
+    /// (shown by default)
+    #[inline]
+    pub fn set_decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    /// Keep the window above all other windows (disabled by default)
+    #[inline]
+    pub fn set_always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = always_on_top;
+        self
+    }
+
+    /// Make the background of the window transparent, so only the content
+    /// painted by the page is visible (disabled by default)
+    ///
+    /// This requires a compositing window manager, otherwise `build` fails
+    /// with `WebviewError::Unsupported`.
+    #[inline]
+    pub fn set_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    /// Set the window icon from encoded image data (e.g. PNG)
+    ///
+    /// If the data can not be decoded, `build` fails with
+    /// `WebviewError::InvalidIcon`.
+    #[inline]
+    pub fn set_icon(mut self, icon: impl Into<Cow<'static, [u8]>>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    #[inline]
+    pub fn set_initial_state(mut self, initial_state: InitialState) -> Self {
+        self.initial_state = initial_state;
+        self
+    }
+
     #[inline]
     pub fn set_debug(mut self, debug: bool) -> Self {
         self.debug = debug;
         self
     }
 
+    /// Set the handler for invocations of `external.invoke` from Javascript
+    ///
+    /// This can either be a closure or a `Router`.
+    #[inline]
+    pub fn set_external_invoke(mut self, func: impl ExternalInvoke<T> + 'invoke) -> Self {
+        self.external_invoke = Some(Box::new(func));
+        self
+    }
+
+    /// Set the handler deciding whether a navigation is allowed
+    ///
+    /// The handler is called for every navigation of the top frame (including
+    /// the initial content) and for requests to open new windows. Only
+    /// supported by the GTK backend.
     #[inline]
-    pub fn set_external_invoke(
+    pub fn set_navigation_handler(
         mut self,
-        func: impl FnMut(&mut Webview, &mut T, &str) + 'invoke,
+        func: impl FnMut(&NavigationRequest) -> Policy + 'invoke,
     ) -> Self {
-        self.external_invoke = Some(Box::new(func));
+        self.navigation_handler = Some(Box::new(func));
+        self
+    }
+
+    /// Register a handler serving all requests for a custom URI scheme
+    ///
+    /// The handler receives the path of each requested URL (e.g.
+    /// `/index.html` for `app://localhost/index.html`) and returns the data
+    /// and MIME type of the response or `None` if nothing exists at the path.
+    /// Schemes are treated as secure origins and each webview of the process
+    /// serves its own schemes. Only supported by the GTK backend.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scheme name is invalid (see RFC 3986, only lowercase
+    /// letters are accepted) or reserved (e.g. `http` or `file`).
+    #[inline]
+    pub fn register_scheme(
+        mut self,
+        scheme: impl Into<String>,
+        func: impl FnMut(&str) -> Option<SchemeResponse> + 'invoke,
+    ) -> Self {
+        let scheme = scheme.into();
+        assert!(
+            scheme::is_valid_scheme(&scheme),
+            "invalid or reserved URI scheme: {:?}",
+            scheme
+        );
+        self.schemes.insert(scheme, Box::new(func));
+        self
+    }
+
+    /// Set the hook called once the DOM of a page is ready
+    ///
+    /// The hook is called for every page that is loaded, the first time
+    /// possibly already during `build`. Only supported by the GTK backend.
+    #[inline]
+    pub fn on_ready(mut self, func: impl FnMut(&mut Webview, &mut T) + 'invoke) -> Self {
+        self.hooks.on_ready = Some(Box::new(func));
+        self
+    }
+
+    /// Set the hook called once a page has finished loading
+    ///
+    /// The hook is called for every page that is loaded, the first time
+    /// possibly already during `build`. Only supported by the GTK backend.
+    #[inline]
+    pub fn on_load_finished(mut self, func: impl FnMut(&mut Webview, &mut T) + 'invoke) -> Self {
+        self.hooks.on_load_finished = Some(Box::new(func));
+        self
+    }
+
+    /// Set the hook called when the user requests to close the window
+    ///
+    /// The window is only closed if the hook returns `true`. Only supported by
+    /// the GTK backend.
+    #[inline]
+    pub fn on_close_requested(
+        mut self,
+        func: impl FnMut(&mut Webview, &mut T) -> bool + 'invoke,
+    ) -> Self {
+        self.hooks.on_close_requested = Some(Box::new(func));
+        self
+    }
+
+    /// Set the hook called once after the main loop started by
+    /// `WebviewHandle::run` has been exited
+    #[inline]
+    pub fn on_exit(mut self, func: impl FnMut(&mut Webview, &mut T) + 'invoke) -> Self {
+        self.hooks.on_exit = Some(Box::new(func));
+        self
+    }
+
+    /// Set the hook called whenever the state of the window changes, e.g. if
+    /// it is minimized or loses the focus
+    ///
+    /// Only supported by the GTK backend.
+    #[inline]
+    pub fn on_window_state_changed(
+        mut self,
+        func: impl FnMut(&mut Webview, &mut T, WindowState) + 'invoke,
+    ) -> Self {
+        self.hooks.on_window_state = Some(Box::new(func));
+        self
+    }
+
+    /// Add a script, which is injected at the start of every page before any
+    /// other script is run
+    ///
+    /// The crate's own bridges are injected first, so `window.rust` is
+    /// already available to the script.
+    #[inline]
+    pub fn add_init_script(self, js: impl Into<String>) -> Self {
+        self.add_user_script(UserScript::new(js))
+    }
+
+    /// Add a script, which is injected into every page
+    #[inline]
+    pub fn add_user_script(mut self, script: UserScript) -> Self {
+        self.user_scripts.push(script);
+        self
+    }
+
+    /// Add a stylesheet, which is injected into every page
+    #[inline]
+    pub fn add_user_style(mut self, style: UserStyle) -> Self {
+        self.user_styles.push(style);
         self
     }
 
@@ -138,7 +418,10 @@ impl<'title, 'content, 'invoke, T> Builder<'title, 'content, 'invoke, T> {
         }
 
         let title = self.title.ok_or(WebviewError::Build)?;
-        let content = self.content.ok_or(WebviewError::Build)?;
+        let content = match self.content.ok_or(WebviewError::Build)? {
+            PendingContent::Url(url) => content::normalize_url(url, self.https_default)?,
+            PendingContent::Resolved(content) => content?,
+        };
         let (width, height) = self.size.unwrap_or((800, 600));
 
         let inner = unsafe {
@@ -160,29 +443,141 @@ impl<'title, 'content, 'invoke, T> Builder<'title, 'content, 'invoke, T> {
                 ffi::struct_webview_set_external_invoke_cb::<T>(&mut webview);
             }
 
-            Webview { webview, buffers }
+            Webview {
+                webview,
+                buffers,
+                queue: Arc::new(DispatchQueue::new()),
+                timers: Timers::new(),
+                assets: None,
+                #[cfg(feature = "server")]
+                server: self.server,
+                https_default: self.https_default,
+                #[cfg(test)]
+                eval_sink: None,
+            }
         };
 
         let mut built = WebviewHandle::new(WebviewWrapper {
             inner,
             ext: Extension {
-                external_invoke: self.external_invoke,
-                userdata:        self.userdata,
+                external_invoke:    self.external_invoke,
+                navigation_handler: self.navigation_handler,
+                schemes:            self.schemes,
+                hooks:              self.hooks,
+                userdata:           self.userdata,
             },
         });
 
+        // schemes must be registered before the initial content is loaded
+        if let Some(assets) = self.assets {
+            built.webview_mut().set_assets(assets)?;
+        }
+
+        let schemes: Vec<String> = built.extension().schemes.keys().cloned().collect();
+        for scheme in schemes {
+            unsafe {
+                ffi::webview_scheme_register::<T>(&mut built.webview_mut().webview, &scheme)?
+            };
+        }
+
         unsafe {
             let inner = built.webview_mut();
             ffi::webview_init(&mut inner.webview)?;
+            inner.queue.open(&mut inner.webview);
+        }
+
+        // the window is already shown, but not drawn before the main loop runs
+        {
+            let inner = built.webview_mut();
+            unsafe {
+                if !self.decorations {
+                    ffi::webview_window_set_decorated(&mut inner.webview, false)?;
+                }
+
+                if self.always_on_top {
+                    ffi::webview_window_set_always_on_top(&mut inner.webview, true)?;
+                }
+
+                if self.transparent {
+                    ffi::webview_window_set_transparent(&mut inner.webview, true)?;
+                }
+
+                if let Some(icon) = self.icon {
+                    ffi::webview_window_set_icon(&mut inner.webview, &icon)?;
+                }
+
+                match self.initial_state {
+                    InitialState::Normal => {}
+                    InitialState::Maximized => {
+                        ffi::webview_window_set_maximized(&mut inner.webview, true)?
+                    }
+                    InitialState::Minimized => {
+                        ffi::webview_window_set_minimized(&mut inner.webview, true)?
+                    }
+                    InitialState::Hidden => {
+                        ffi::webview_window_set_visible(&mut inner.webview, false)?
+                    }
+                };
+            }
+        }
+
+        // the initial content is loaded asynchronously, so user content added
+        // right after initialization still applies to it; the bridges are
+        // user scripts as well, so they are re-injected on every navigation,
+        // and are added first, so init scripts can already use `window.rust`
+        inject_bridge(&mut built, event::JS_SHIM)?;
+        let init_script = built
+            .extension()
+            .external_invoke
+            .as_ref()
+            .and_then(|func| func.init_script());
+        if let Some(script) = init_script {
+            inject_bridge(&mut built, &script)?;
+        }
+
+        for script in self.user_scripts {
+            built.add_user_script(script)?;
+        }
+
+        for style in self.user_styles {
+            built.add_user_style(style)?;
+        }
+
+        if built.extension().navigation_handler.is_some() {
+            unsafe { ffi::webview_policy_connect::<T>(&mut built.webview_mut().webview) };
+        }
+
+        if built.extension().hooks.requires_connect() {
+            unsafe { ffi::webview_lifecycle_connect::<T>(&mut built.webview_mut().webview) };
+        }
+
+        if built.extension().hooks.on_window_state.is_some() {
+            unsafe { ffi::webview_window_state_connect::<T>(&mut built.webview_mut().webview) };
         }
 
         Ok(built)
     }
 }
 
+/// Injects the Javascript shim of one of the crate's bridges into every page.
+///
+/// The shim is added as a user script, since a shim evaluated with `eval`
+/// only applies to the current page and is lost after any navigation or
+/// reload, which leaves pending calls unsettled. Evaluating it would also block
+/// until the initial content has been loaded. Platforms without user scripts
+/// fall back to evaluating the shim once.
+#[inline]
+fn inject_bridge<T>(webview: &mut WebviewHandle<T>, js: &str) -> Result<(), WebviewError> {
+    let script = UserScript::new(js).set_main_frame_only(true);
+    match webview.add_user_script(script) {
+        Err(WebviewError::Unsupported(_)) => webview.eval(js),
+        result => result,
+    }
+}
+
 #[inline]
 fn is_main_thread() -> bool {
     thread::current()
         .name()
-        .map_or(false, |name| name == "main")
+        .is_some_and(|name| name == "main")
 }
@@ -1,10 +1,40 @@
 use std::ffi::CStr;
-use std::mem;
-use std::os::raw::{c_char, c_void};
+use std::os::raw::{c_char, c_int, c_void};
+use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::dialog;
+use crate::navigation::{NavigationRequest, NavigationType};
+use crate::scheme;
+use crate::timer::Timers;
+use crate::window::WindowState;
 use crate::{Webview, WebviewWrapper};
 use webview_sys as sys;
 
+/// Handler for invocations of `external.invoke` from Javascript
+///
+/// This is implemented for all closures receiving the webview, the userdata and
+/// the invocation argument as well as for the `Router`.
+pub trait ExternalInvoke<T> {
+    fn invoke(&mut self, webview: &mut Webview, userdata: &mut T, arg: &str);
+
+    /// Javascript code to be injected at the start of every page
+    #[inline]
+    fn init_script(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<T, F> ExternalInvoke<T> for F
+where
+    F: FnMut(&mut Webview, &mut T, &str),
+{
+    #[inline]
+    fn invoke(&mut self, webview: &mut Webview, userdata: &mut T, arg: &str) {
+        self(webview, userdata, arg);
+    }
+}
+
 /// Extern function for C callback
 ///
 /// The C library calls this function, which in turn executes the provided
@@ -26,23 +56,164 @@ pub extern "system" fn invoke_handler<'invoke, T>(webview: *mut sys::webview, ar
         let cow = CStr::from_ptr(arg).to_string_lossy();
         let arg = cow.as_ref();
 
-        func(&mut (*wrapper).inner, userdata, arg);
+        func.invoke(&mut (*wrapper).inner, userdata, arg);
     }
 }
 
 /// Extern function for C callback
 ///
-/// The C library calls this function, which in turn executes the provided
-/// Closure
-pub extern "system" fn dispatch_handler<'invoke, T>(webview: *mut sys::webview, args: *mut c_void) {
+/// The C library calls this function on the main thread after a job has been
+/// pushed to the webview's dispatch queue, which is then drained.
+pub extern "system" fn dispatch_handler(webview: *mut sys::webview, _: *mut c_void) {
     unsafe {
-        let wrapper = webview as *mut WebviewWrapper<'invoke, T>;
-        let webview = &mut (*wrapper).inner;
-        let userdata = &mut (*wrapper).ext.userdata;
+        // the queue is cloned, since the jobs require mutable access to the
+        // webview
+        let queue = Arc::clone(&(*(webview as *mut Webview)).queue);
+        queue.drain(webview);
+    }
+}
+
+/// Extern function for C callback
+///
+/// The C library calls this function on the main thread once the interval of
+/// a timer has elapsed, which in turn executes the timer's closure.
+/// The timer is removed by the C library if 0 is returned.
+pub extern "system" fn timer_handler(webview: *mut sys::webview, arg: *mut c_void) -> c_int {
+    unsafe {
+        let timers: *mut Timers = &mut (*(webview as *mut Webview)).timers;
+        Timers::fire(timers, webview, arg as usize as u64) as c_int
+    }
+}
+
+/// Extern function for C callback
+///
+/// The C library calls this function on the main thread for each lifecycle
+/// event, which in turn executes the respective hook.
+/// Closing the window is vetoed if 0 is returned for a close request.
+pub extern "system" fn lifecycle_handler<T>(webview: *mut sys::webview, event: c_int) -> c_int {
+    unsafe {
+        let wrapper = &mut *(webview as *mut WebviewWrapper<T>);
+        let (hooks, userdata) = (&mut wrapper.ext.hooks, &mut wrapper.ext.userdata);
+        let webview = &mut wrapper.inner;
 
-        //The `Send` bound variant is used here so the function can be used for
-        // dispatches from the main thread as well as any other thread.
-        let func: &mut &mut (FnMut(&mut Webview, &mut T) + Send) = mem::transmute(args);
-        func(webview, userdata);
+        match event {
+            sys::WEBVIEW_EVENT_READY => {
+                if let Some(ref mut func) = hooks.on_ready {
+                    func(webview, userdata);
+                }
+            }
+            sys::WEBVIEW_EVENT_LOAD_FINISHED => {
+                if let Some(ref mut func) = hooks.on_load_finished {
+                    func(webview, userdata);
+                }
+            }
+            sys::WEBVIEW_EVENT_CLOSE_REQUESTED => {
+                if let Some(ref mut func) = hooks.on_close_requested {
+                    return func(webview, userdata) as c_int;
+                }
+            }
+            _ => {}
+        }
+
+        1
+    }
+}
+
+/// Extern function for C callback
+///
+/// The C library calls this function whenever the state of the window
+/// changes, which in turn executes the respective hook.
+pub extern "system" fn window_state_handler<T>(webview: *mut sys::webview, state: c_int) {
+    unsafe {
+        let wrapper = &mut *(webview as *mut WebviewWrapper<T>);
+        let (hooks, userdata) = (&mut wrapper.ext.hooks, &mut wrapper.ext.userdata);
+
+        if let Some(ref mut func) = hooks.on_window_state {
+            let state = WindowState::from_bits_truncate(state);
+            func(&mut wrapper.inner, userdata, state);
+        }
+    }
+}
+
+/// Extern function for C callback
+///
+/// The C library calls this function for every navigation, which in turn
+/// executes the navigation handler and returns its decision.
+pub extern "system" fn navigation_handler<T>(
+    webview: *mut sys::webview,
+    url: *const c_char,
+    navigation_type: c_int,
+    new_window: c_int,
+) -> c_int {
+    unsafe {
+        let wrapper = &mut *(webview as *mut WebviewWrapper<T>);
+        let func = wrapper
+            .ext
+            .navigation_handler
+            .as_mut()
+            .expect("no navigation handler set");
+
+        let cow = CStr::from_ptr(url).to_string_lossy();
+        let request = NavigationRequest::new(
+            cow.as_ref(),
+            NavigationType::from(navigation_type),
+            new_window != 0,
+        );
+
+        func(&request).as_raw()
+    }
+}
+
+/// Extern function for C callback
+///
+/// The C library calls this function for every request of a registered custom
+/// URI scheme, which in turn executes the respective handler and answers the
+/// request.
+pub extern "system" fn scheme_handler<T>(
+    webview: *mut sys::webview,
+    scheme: *const c_char,
+    path: *const c_char,
+    request: *mut c_void,
+) {
+    unsafe {
+        let wrapper = &mut *(webview as *mut WebviewWrapper<T>);
+        let scheme = CStr::from_ptr(scheme).to_string_lossy();
+        let path = CStr::from_ptr(path).to_string_lossy();
+
+        let response = wrapper
+            .ext
+            .schemes
+            .get_mut(scheme.as_ref())
+            .and_then(|func| func(path.as_ref()));
+
+        scheme::respond(request, response);
+    }
+}
+
+/// Extern function for C callback
+///
+/// The C library calls this function for every request of the internal scheme
+/// serving the `Assets` of a webview.
+pub extern "system" fn assets_handler(
+    webview: *mut sys::webview,
+    _: *const c_char,
+    path: *const c_char,
+    request: *mut c_void,
+) {
+    unsafe {
+        let webview = &*(webview as *mut Webview);
+        let path = CStr::from_ptr(path).to_string_lossy();
+        scheme::respond_asset(request, webview.assets.as_ref(), path.as_ref());
+    }
+}
+
+/// Extern function for C callback
+///
+/// The C library calls this function for each path selected in a file dialog,
+/// which is collected into the vector passed as argument.
+pub extern "system" fn file_dialog_handler(paths: *mut c_void, path: *const c_char) {
+    unsafe {
+        let paths = &mut *(paths as *mut Vec<PathBuf>);
+        paths.push(dialog::bytes_to_path(CStr::from_ptr(path).to_bytes()));
     }
 }
@@ -1,101 +1,587 @@
 use std::borrow::Cow;
 use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::assets::{Assets, ASSETS_URL};
+use crate::error::WebviewError;
+#[cfg(feature = "server")]
+use crate::server::AssetServer;
 
 /// Content Variants
 ///
-/// The initial content for a webview can be either a URL, a filepath or HTML
-/// markup.
+/// The initial content for a webview can be either a URL, a filepath, HTML
+/// markup or a bundle of embedded assets.
 pub enum Content<'content, C>
 where
     C: Into<Cow<'content, str>>,
 {
     Url(C),
     File(C),
+    /// HTML markup, which is percent-encoded into a data URL
     Html(C),
+    /// HTML markup, which is base64-encoded into a data URL (more compact for
+    /// markup with many non-ASCII characters)
+    HtmlBase64(C),
+    /// Assets served through an internal custom URI scheme (GTK only)
+    Assets(Assets),
+    /// Content served by a loopback HTTP server, which is kept running as
+    /// long as the webview exists
+    #[cfg(feature = "server")]
+    Served(AssetServer),
     #[doc(hidden)]
     __Hidden(PhantomData<&'content str>),
 }
 
-impl<'content, C> Into<Cow<'content, str>> for Content<'content, C>
+impl<'content, C> Content<'content, C>
 where
     C: Into<Cow<'content, str>>,
 {
-    /// Conversion from Content into a string (using the copy-on-write type)
+    /// Conversion from Content into a URL (using the copy-on-write type)
+    ///
+    /// Assets are converted to the URL at which they are served, the assets
+    /// themselves must be extracted beforehand. Served content must always be
+    /// extracted by the caller, since the server has to be kept alive along
+    /// with the webview, so this conversion is not public.
     #[inline]
-    fn into(self) -> Cow<'content, str> {
+    pub(crate) fn into_url(self) -> Cow<'content, str> {
         match self {
             Content::Url(content) => into_url(content),
             Content::File(content) => into_file_path(content),
-            Content::Html(content) => into_html(content),
+            Content::Html(content) => into_html(content, HtmlEncoding::Percent),
+            Content::HtmlBase64(content) => into_html(content, HtmlEncoding::Base64),
+            Content::Assets(_) => Cow::Borrowed(ASSETS_URL),
+            #[cfg(feature = "server")]
+            Content::Served(_) => unreachable!("served content is extracted by the caller"),
             Content::__Hidden(_) => panic!("enum variant for internal use only"),
         }
     }
 }
 
 /// Convert Content string into correctly formatted URL.
+///
+/// Bare hosts (e.g. `example.com`) are prefixed with `http://`, all other
+/// strings are left untouched. Use `normalize_url` for validation.
 fn into_url<'s>(content: impl Into<Cow<'s, str>>) -> Cow<'s, str> {
     let content = content.into();
-    match content {
-        Cow::Borrowed(string) => {
-            if string_starts_with_any(string, &["http://", "https://"]) {
-                Cow::from(string)
-            } else {
-                Cow::from(format!("http://{}", string))
-            }
+    match parse_url(&content) {
+        Ok(ParsedUrl::BareHost) => Cow::from(format!("http://{}", content)),
+        _ => content,
+    }
+}
+
+/// Validates an absolute URL.
+///
+/// Bare hosts with an optional port and path (e.g. `localhost:8080/index`)
+/// are prefixed with `https://` or `http://`, depending on `https`.
+pub(crate) fn normalize_url<'s>(
+    url: Cow<'s, str>,
+    https: bool,
+) -> Result<Cow<'s, str>, WebviewError> {
+    match parse_url(&url) {
+        Ok(ParsedUrl::Absolute) => Ok(url),
+        Ok(ParsedUrl::BareHost) => {
+            let scheme = if https { "https://" } else { "http://" };
+            Ok(Cow::from(format!("{}{}", scheme, url)))
         }
-        Cow::Owned(mut string) => {
-            if string_starts_with_any(&string, &["http://", "https://"]) {
-                Cow::from(string)
-            } else {
-                string.insert_str(0, "http://");
-                Cow::from(string)
-            }
+        Err(()) => Err(WebviewError::InvalidUrl(url.into_owned())),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ParsedUrl {
+    Absolute,
+    BareHost,
+}
+
+/// Parses a URL (RFC 3986 with non-ASCII characters allowed) and checks
+/// whether it is absolute or a bare host without a scheme.
+fn parse_url(url: &str) -> Result<ParsedUrl, ()> {
+    if url.is_empty() || url.chars().any(|ch| ch.is_whitespace() || ch.is_control()) {
+        return Err(());
+    }
+
+    if let Some(idx) = url.find(':') {
+        let (scheme, rest) = (&url[..idx], &url[idx + 1..]);
+        // `localhost:8080` is a host with a port, not a URL with a scheme
+        let is_port = rest
+            .split(['/', '?', '#'])
+            .next()
+            .is_some_and(|port| !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()));
+
+        if is_scheme(scheme) && !is_port {
+            let scheme = scheme.to_ascii_lowercase();
+            return match (rest.starts_with("//"), scheme.as_str()) {
+                (true, "file") => parse_authority(&rest[2..], true).map(|_| ParsedUrl::Absolute),
+                (true, _) => parse_authority(&rest[2..], false).map(|_| ParsedUrl::Absolute),
+                (false, "http") | (false, "https") | (false, "ws") | (false, "wss") => Err(()),
+                (false, _) if rest.is_empty() => Err(()),
+                (false, _) => Ok(ParsedUrl::Absolute),
+            };
+        }
+    }
+
+    parse_authority(url, false).map(|_| ParsedUrl::BareHost)
+}
+
+/// Returns `true` if the string is a valid URL scheme.
+fn is_scheme(string: &str) -> bool {
+    let mut chars = string.chars();
+    chars.next().is_some_and(|ch| ch.is_ascii_alphabetic())
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '+' || ch == '-' || ch == '.')
+}
+
+/// Parses the authority (`user@host:port`) at the start of the remaining URL.
+fn parse_authority(string: &str, allow_empty: bool) -> Result<(), ()> {
+    let end = string.find(['/', '?', '#']).unwrap_or(string.len());
+    let authority = &string[..end];
+    let host_port = match authority.rfind('@') {
+        Some(idx) => &authority[idx + 1..],
+        None => authority,
+    };
+
+    let (host, port) = if host_port.starts_with('[') {
+        let idx = host_port.find(']').ok_or(())?;
+        let ipv6 = &host_port[1..idx];
+        if ipv6.is_empty()
+            || !ipv6
+                .chars()
+                .all(|ch| ch.is_ascii_hexdigit() || ch == ':' || ch == '.')
+        {
+            return Err(());
+        }
+
+        match &host_port[idx + 1..] {
+            "" => (ipv6, None),
+            rest if rest.starts_with(':') => (ipv6, Some(&rest[1..])),
+            _ => return Err(()),
+        }
+    } else {
+        match host_port.rfind(':') {
+            Some(idx) => (&host_port[..idx], Some(&host_port[idx + 1..])),
+            None => (host_port, None),
         }
+    };
+
+    if host.is_empty() && !allow_empty {
+        return Err(());
+    }
+
+    let valid_host = host
+        .chars()
+        .all(|ch| !ch.is_ascii() || ch.is_ascii_alphanumeric() || "-._~%!$&'()*+,;=:".contains(ch));
+
+    let valid_port =
+        port.is_none_or(|port| port.is_empty() || (port.len() <= 5 && port.parse::<u16>().is_ok()));
+
+    if valid_host && valid_port {
+        Ok(())
+    } else {
+        Err(())
     }
 }
 
+/// Convert Content string into a `file://` URL.
+///
+/// Strings which already are file URLs are left untouched. Paths which can
+/// not be resolved are still encoded, so the error is left to the webview.
 fn into_file_path<'s>(content: impl Into<Cow<'s, str>>) -> Cow<'s, str> {
     let content = content.into();
-    match content {
-        Cow::Borrowed(string) => {
-            if string.starts_with("file:///") {
-                Cow::from(string)
-            } else {
-                Cow::from(format!("file:///{}", string))
-            }
+    if content.starts_with("file:") {
+        return content;
+    }
+
+    let url = match file_url(Path::new(content.as_ref())) {
+        Ok(url) => url,
+        Err(_) => encode_file_url(Path::new(content.as_ref())),
+    };
+
+    Cow::from(url)
+}
+
+/// Resolves a file path into a `file://` URL.
+///
+/// The path is canonicalized (relative paths are resolved against the
+/// current working directory), so the file must exist.
+pub(crate) fn file_url(path: &Path) -> Result<String, WebviewError> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|_| WebviewError::InvalidPath(path.to_path_buf()))?;
+
+    Ok(encode_file_url(&canonical))
+}
+
+fn encode_file_url(path: &Path) -> String {
+    let bytes = path_bytes(path);
+    let mut url = String::with_capacity(bytes.len() + 8);
+    url.push_str("file:");
+    // UNC paths (`//server/share`) already start with the authority
+    if !bytes.starts_with(b"//") {
+        url.push_str("//");
+    }
+    if bytes.first() != Some(&b'/') {
+        url.push('/');
+    }
+
+    push_percent_encoded(&mut url, &bytes);
+    url
+}
+
+#[cfg(unix)]
+fn path_bytes<'p>(path: &'p Path) -> Cow<'p, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+#[cfg(not(unix))]
+fn path_bytes<'p>(path: &'p Path) -> Cow<'p, [u8]> {
+    Cow::Owned(windows_path(&path.to_string_lossy()).into_bytes())
+}
+
+/// Converts a windows path into a path with forward slashes.
+///
+/// Canonical paths on windows have the verbatim prefix `\\?\`, which is
+/// removed, verbatim UNC paths (`\\?\UNC\server\share`) are converted to
+/// `//server/share`.
+#[cfg(any(not(unix), test))]
+fn windows_path(path: &str) -> String {
+    let path = match path.strip_prefix(r"\\?\UNC\") {
+        Some(unc) => format!(r"\\{}", unc),
+        None => path.trim_start_matches(r"\\?\").to_owned(),
+    };
+
+    path.replace('\\', "/")
+}
+
+/// Encoding of HTML markup in data URLs
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HtmlEncoding {
+    Percent,
+    Base64,
+}
+
+const DATA_URL_PREFIX: &str = "data:text/html";
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Convert HTML markup into an encoded data URL with UTF-8 charset.
+///
+/// Strings which already are HTML data URLs are left untouched.
+fn into_html<'s>(content: impl Into<Cow<'s, str>>, encoding: HtmlEncoding) -> Cow<'s, str> {
+    let content = content.into();
+    if content.starts_with(DATA_URL_PREFIX) {
+        return content;
+    }
+
+    let mut url = String::with_capacity(content.len() + 32);
+    url.push_str(DATA_URL_PREFIX);
+    match encoding {
+        HtmlEncoding::Percent => {
+            url.push_str(";charset=utf-8,");
+            push_percent_encoded(&mut url, content.as_bytes());
         }
-        Cow::Owned(mut string) => {
-            if string.starts_with("file:///") {
-                Cow::from(string)
-            } else {
-                string.insert_str(0, "file:///");
-                Cow::from(string)
-            }
+        HtmlEncoding::Base64 => {
+            url.push_str(";charset=utf-8;base64,");
+            push_base64(&mut url, content.as_bytes());
         }
+    };
+
+    Cow::from(url)
+}
+
+/// Percent-encodes all bytes which are not allowed or have a special meaning
+/// in URLs, including `%`, `#`, `?` and all non-ASCII bytes.
+fn push_percent_encoded(url: &mut String, bytes: &[u8]) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+    url.reserve(bytes.len());
+    for &byte in bytes {
+        match byte {
+            0x00..=0x20 | 0x7f..=0xff => {}
+            b'"' | b'#' | b'%' | b'?' | b'<' | b'>' | b'\\' | b'^' | b'`' | b'{' | b'|' | b'}' => {}
+            _ => {
+                url.push(byte as char);
+                continue;
+            }
+        };
+
+        url.push('%');
+        url.push(HEX[(byte >> 4) as usize] as char);
+        url.push(HEX[(byte & 0xf) as usize] as char);
     }
 }
 
-fn into_html<'s>(content: impl Into<Cow<'s, str>>) -> Cow<'s, str> {
-    let content = content.into();
-    match content {
-        Cow::Borrowed(string) => {
-            if string.starts_with("data:text/html,") {
-                Cow::from(string)
-            } else {
-                Cow::from(format!("data:text/html,{}", string))
+/// Decodes percent-encoded octets, returns `None` for invalid escapes or if
+/// the result is not valid UTF-8.
+pub(crate) fn percent_decode(string: &str) -> Option<String> {
+    let bytes = string.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'%' => {
+                let hex = string.get(idx + 1..idx + 3)?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                idx += 3;
+            }
+            byte => {
+                decoded.push(byte);
+                idx += 1;
             }
         }
-        Cow::Owned(mut string) => {
-            if string.starts_with("data:text/html,") {
-                Cow::from(string)
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+/// Encodes bytes as (padded) base64.
+fn push_base64(url: &mut String, bytes: &[u8]) {
+    url.reserve(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).cloned().unwrap_or(0),
+            chunk.get(2).cloned().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                let sextet = (n >> (18 - 6 * idx)) & 0x3f;
+                url.push(BASE64_CHARS[sextet as usize] as char);
             } else {
-                string.insert_str(0, "data:text/html,");
-                Cow::from(string)
+                url.push('=');
             }
         }
     }
 }
 
-fn string_starts_with_any(string: &str, any: &[&str]) -> bool {
-    any.iter().any(|&contain| string.starts_with(contain))
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+    use std::env;
+    use std::fs;
+
+    use crate::content::*;
+
+    fn html(markup: &str) -> String {
+        let url: Cow<str> = Content::Html(markup).into_url();
+        url.into_owned()
+    }
+
+    fn html_base64(markup: &str) -> String {
+        let url: Cow<str> = Content::HtmlBase64(markup).into_url();
+        url.into_owned()
+    }
+
+    fn base64_decode(encoded: &str) -> String {
+        let mut decoded = Vec::new();
+        for chunk in encoded.as_bytes().chunks(4) {
+            let mut n = 0u32;
+            let mut len = 0;
+            for &byte in chunk {
+                n <<= 6;
+                if byte != b'=' {
+                    n |= BASE64_CHARS.iter().position(|&ch| ch == byte).unwrap() as u32;
+                    len += 1;
+                }
+            }
+
+            let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+            decoded.extend_from_slice(&bytes[..len - 1]);
+        }
+
+        String::from_utf8(decoded).unwrap()
+    }
+
+    #[test]
+    fn html_fragments() {
+        let markup = "<a href=\"#top\">100% 🦀</a>\n";
+        let url = html(markup);
+
+        let body = &url["data:text/html;charset=utf-8,".len()..];
+        assert!(url.starts_with("data:text/html;charset=utf-8,"));
+        assert!(!body.contains('#') && !body.contains(' ') && !body.contains('\n'));
+        assert!(body.is_ascii());
+        assert_eq!(
+            body,
+            "%3Ca%20href=%22%23top%22%3E100%25%20%F0%9F%A6%80%3C/a%3E%0A"
+        );
+        assert_eq!(percent_decode(body).unwrap(), markup);
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(
+            percent_decode("/a%20b/%C3%BC"),
+            Some(String::from("/a b/ü"))
+        );
+        assert_eq!(percent_decode("/100%"), None);
+        assert_eq!(percent_decode("/%zz"), None);
+        assert_eq!(percent_decode("/%ff"), None);
+    }
+
+    #[test]
+    fn html_base64_encoding() {
+        for markup in &["", "a", "ab", "abc", "<p>#%🦀</p>"] {
+            let url = html_base64(markup);
+            let body = &url["data:text/html;charset=utf-8;base64,".len()..];
+            assert_eq!(body.len() % 4, 0);
+            assert_eq!(base64_decode(body), *markup);
+        }
+
+        assert_eq!(
+            html_base64("🦀"),
+            "data:text/html;charset=utf-8;base64,8J+mgA=="
+        );
+    }
+
+    #[test]
+    fn html_data_url_untouched() {
+        let url = "data:text/html;charset=utf-8,%3Cp%3E";
+        let content: Cow<str> = Content::Html(url).into_url();
+        match content {
+            Cow::Borrowed(string) => assert_eq!(string, url),
+            _ => panic!("unnecessary allocation"),
+        };
+    }
+
+    fn url(url: &str, https: bool) -> Result<String, ()> {
+        normalize_url(Cow::from(url), https)
+            .map(Cow::into_owned)
+            .map_err(|_| ())
+    }
+
+    #[test]
+    fn urls() {
+        let absolute = [
+            "https://example.com",
+            "http://user:pw@example.com:8080/path?query=1#fragment",
+            "http://127.0.0.1:3000/",
+            "http://[::1]:8080/index.html",
+            "https://bücher.example/ü",
+            "file:///home/user/index.html",
+            "data:text/html,%3Cp%3E",
+            "about:blank",
+            "mailto:user@example.com",
+            "webview-rs://assets/",
+            "custom+scheme:path",
+        ];
+
+        for &absolute in &absolute {
+            assert_eq!(url(absolute, true), Ok(absolute.to_owned()));
+            assert_eq!(url(absolute, false), Ok(absolute.to_owned()));
+        }
+
+        assert_eq!(
+            url("example.com", true),
+            Ok("https://example.com".to_owned())
+        );
+        assert_eq!(
+            url("example.com", false),
+            Ok("http://example.com".to_owned())
+        );
+        assert_eq!(
+            url("localhost:8080/app", true),
+            Ok("https://localhost:8080/app".to_owned())
+        );
+        assert_eq!(url("[::1]:80", false), Ok("http://[::1]:80".to_owned()));
+    }
+
+    #[test]
+    fn invalid_urls() {
+        let invalid = [
+            "",
+            "http://",
+            "https:///path",
+            "http:example.com",
+            "http://example .com",
+            "http://example.com:99999",
+            "http://example.com:80a",
+            "http://[::1",
+            "http://[zz]/",
+            "example.com/a b",
+            "/relative/path",
+            "about:",
+            "http://exa<mple.com",
+            "line\nbreak.com",
+        ];
+
+        for &invalid in &invalid {
+            assert_eq!(url(invalid, true), Err(()), "{}", invalid);
+        }
+
+        match normalize_url(Cow::from("http://"), false) {
+            Err(WebviewError::InvalidUrl(url)) => assert_eq!(url, "http://"),
+            _ => panic!("expected invalid URL"),
+        };
+    }
+
+    #[test]
+    fn url_content() {
+        let content: Cow<str> = Content::Url("about:blank").into_url();
+        assert_eq!(content, "about:blank");
+        let content: Cow<str> = Content::Url("example.com").into_url();
+        assert_eq!(content, "http://example.com");
+    }
+
+    #[test]
+    fn file_urls() {
+        let dir = env::temp_dir().join("webview_rs content 100%");
+        let file = dir.join("#index ü.html");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&file, "<p></p>").unwrap();
+
+        let url = file_url(&file).unwrap();
+        let canonical = file.canonicalize().unwrap();
+        assert!(url.starts_with("file:///"));
+        assert!(url.ends_with("/webview_rs%20content%20100%25/%23index%20%C3%BC.html"));
+        assert_eq!(
+            percent_decode(&url["file://".len()..]).unwrap(),
+            canonical.to_str().unwrap()
+        );
+
+        let missing = dir.join("missing.html");
+        match file_url(&missing) {
+            Err(WebviewError::InvalidPath(path)) => assert_eq!(path, missing),
+            _ => panic!("missing file must be an invalid path"),
+        };
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn windows_paths() {
+        assert_eq!(windows_path(r"\\?\C:\dir\index.html"), "C:/dir/index.html");
+        assert_eq!(windows_path(r"C:\index.html"), "C:/index.html");
+        assert_eq!(
+            windows_path(r"\\?\UNC\server\share\index.html"),
+            "//server/share/index.html"
+        );
+        assert_eq!(windows_path(r"\\server\share"), "//server/share");
+    }
+
+    #[test]
+    fn file_url_untouched() {
+        let url = "file:///tmp/index.html";
+        let content: Cow<str> = Content::File(url).into_url();
+        assert_eq!(content, url);
+    }
+
+    #[test]
+    fn html_large_document() {
+        let markup: String = (0..100_000)
+            .map(|idx| format!("<p id=\"p{}\">#{} 50% ü</p>", idx, idx))
+            .collect();
+
+        let url = html(&markup);
+        assert_eq!(
+            percent_decode(&url["data:text/html;charset=utf-8,".len()..]).unwrap(),
+            markup
+        );
+
+        let url = html_base64(&markup);
+        let body = &url["data:text/html;charset=utf-8;base64,".len()..];
+        assert_eq!(body.len(), markup.len().div_ceil(3) * 4);
+        assert_eq!(base64_decode(body), markup);
+    }
 }
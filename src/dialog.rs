@@ -0,0 +1,318 @@
+//! Module for typed native dialogs.
+//!
+//! The C library writes the path selected in a file dialog as a NUL-terminated
+//! string into a buffer supplied by the caller, which is left untouched if the
+//! dialog is cancelled. The buffer is managed and parsed here.
+//!
+//! The more flexible `FileDialog` does not require a buffer, since the
+//! selected paths are passed to `callback::file_dialog_handler` instead.
+
+use std::ffi::CString;
+use std::os::raw::c_int;
+use std::path::{Path, PathBuf};
+
+use crate::error::WebviewError;
+use crate::ffi::Flags;
+use webview_sys as sys;
+
+/// The size of the buffer for paths returned by dialogs, which is larger than
+/// `PATH_MAX` (including the NUL terminator) on Linux, so truncated paths can
+/// be detected
+pub(crate) const PATH_BUFFER_SIZE: usize = 4097;
+
+/// The severity of an alert dialog
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl AlertLevel {
+    #[inline]
+    pub(crate) fn as_flags(self) -> Flags {
+        match self {
+            AlertLevel::Info => Flags::Info,
+            AlertLevel::Warning => Flags::Warning,
+            AlertLevel::Error => Flags::Error,
+        }
+    }
+}
+
+/// The kind of a `FileDialog`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileDialogKind {
+    Open,
+    OpenDirectory,
+    Save,
+}
+
+impl FileDialogKind {
+    #[inline]
+    pub(crate) fn as_raw(self) -> c_int {
+        match self {
+            FileDialogKind::Open => sys::WEBVIEW_FILE_DIALOG_OPEN,
+            FileDialogKind::OpenDirectory => sys::WEBVIEW_FILE_DIALOG_OPEN_DIRECTORY,
+            FileDialogKind::Save => sys::WEBVIEW_FILE_DIALOG_SAVE,
+        }
+    }
+}
+
+/// A configurable dialog for selecting files or directories
+///
+/// The dialog is shown by `Webview::show_file_dialog`, which returns the
+/// selected paths (or none, if the dialog has been cancelled). Currently only
+/// supported by the GTK backend.
+///
+/// # Examples
+///
+/// ```ignore
+/// let dialog = FileDialog::open()
+///     .set_title("Open Images")
+///     .add_filter("Images", &["*.png", "*.jpg"])
+///     .set_multiple(true);
+///
+/// for path in webview.show_file_dialog(&dialog)? {
+///     println!("{}", path.display());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDialog {
+    pub(crate) kind:              FileDialogKind,
+    pub(crate) title:             Option<String>,
+    pub(crate) filters:           Vec<(String, Vec<String>)>,
+    pub(crate) directory:         Option<PathBuf>,
+    pub(crate) file_name:         Option<String>,
+    pub(crate) multiple:          bool,
+    pub(crate) confirm_overwrite: bool,
+}
+
+impl FileDialog {
+    #[inline]
+    pub fn new(kind: FileDialogKind) -> Self {
+        Self {
+            kind,
+            title: None,
+            filters: Vec::new(),
+            directory: None,
+            file_name: None,
+            multiple: false,
+            confirm_overwrite: true,
+        }
+    }
+
+    /// Creates a dialog for selecting files to open
+    #[inline]
+    pub fn open() -> Self {
+        Self::new(FileDialogKind::Open)
+    }
+
+    /// Creates a dialog for selecting directories
+    #[inline]
+    pub fn open_directory() -> Self {
+        Self::new(FileDialogKind::OpenDirectory)
+    }
+
+    /// Creates a dialog for selecting the path to save a file at
+    #[inline]
+    pub fn save() -> Self {
+        Self::new(FileDialogKind::Save)
+    }
+
+    #[inline]
+    pub fn set_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Adds a named filter with glob patterns (e.g. `*.png`), the first filter
+    /// is selected initially.
+    #[inline]
+    pub fn add_filter(mut self, name: impl Into<String>, patterns: &[&str]) -> Self {
+        let patterns = patterns.iter().map(|&pattern| pattern.to_owned()).collect();
+        self.filters.push((name.into(), patterns));
+        self
+    }
+
+    /// Sets the directory shown initially.
+    #[inline]
+    pub fn set_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Sets the suggested file name (only used by save dialogs).
+    #[inline]
+    pub fn set_file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// Allows selecting multiple files or directories (not used by save
+    /// dialogs, disabled by default).
+    #[inline]
+    pub fn set_multiple(mut self, multiple: bool) -> Self {
+        self.multiple = multiple;
+        self
+    }
+
+    /// Asks the user for confirmation if an existing file is selected (only
+    /// used by save dialogs, enabled by default).
+    #[inline]
+    pub fn set_confirm_overwrite(mut self, confirm_overwrite: bool) -> Self {
+        self.confirm_overwrite = confirm_overwrite;
+        self
+    }
+
+    /// Returns the title of the dialog, with a default based on its kind.
+    #[inline]
+    pub(crate) fn title(&self) -> &str {
+        match (self.title.as_ref(), self.kind, self.multiple) {
+            (Some(title), _, _) => title,
+            (None, FileDialogKind::Open, false) => "Open File",
+            (None, FileDialogKind::Open, true) => "Open Files",
+            (None, FileDialogKind::OpenDirectory, _) => "Open Directory",
+            (None, FileDialogKind::Save, _) => "Save File",
+        }
+    }
+
+    /// Returns the names and the `;`-separated patterns of all filters.
+    #[inline]
+    pub(crate) fn filter_strings(&self) -> Result<(Vec<CString>, Vec<CString>), WebviewError> {
+        let mut names = Vec::with_capacity(self.filters.len());
+        let mut patterns = Vec::with_capacity(self.filters.len());
+        for (name, filter_patterns) in &self.filters {
+            names.push(CString::new(name.as_str())?);
+            patterns.push(CString::new(filter_patterns.join(";"))?);
+        }
+
+        Ok((names, patterns))
+    }
+}
+
+/// Creates a zeroed buffer for the result of a file dialog.
+#[inline]
+pub(crate) fn path_buffer() -> Vec<u8> {
+    vec![0; PATH_BUFFER_SIZE]
+}
+
+/// Parses the path written into the (initially zeroed) buffer by a file
+/// dialog, returning `None` if the dialog has been cancelled.
+///
+/// # Errors
+///
+/// A `WebviewError::TruncatedPath` is returned if the path fills the entire
+/// buffer, since it has most likely been truncated.
+#[inline]
+pub(crate) fn parse_path(buffer: &[u8]) -> Result<Option<PathBuf>, WebviewError> {
+    let len = match buffer.iter().position(|&byte| byte == 0) {
+        Some(len) if len + 1 < buffer.len() => len,
+        _ => return Err(WebviewError::TruncatedPath),
+    };
+
+    match len {
+        0 => Ok(None),
+        _ => Ok(Some(bytes_to_path(&buffer[..len]))),
+    }
+}
+
+#[cfg(unix)]
+#[inline]
+pub(crate) fn bytes_to_path(bytes: &[u8]) -> PathBuf {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+#[inline]
+pub(crate) fn bytes_to_path(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(unix)]
+#[inline]
+pub(crate) fn path_to_cstring(path: &Path) -> Result<CString, WebviewError> {
+    use std::os::unix::ffi::OsStrExt;
+    Ok(CString::new(path.as_os_str().as_bytes())?)
+}
+
+#[cfg(not(unix))]
+#[inline]
+pub(crate) fn path_to_cstring(path: &Path) -> Result<CString, WebviewError> {
+    Ok(CString::new(path.to_string_lossy().into_owned())?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::dialog::*;
+
+    fn buffer(path: &[u8]) -> Vec<u8> {
+        let mut buffer = path_buffer();
+        buffer[..path.len()].copy_from_slice(path);
+        buffer
+    }
+
+    #[test]
+    fn cancelled() {
+        assert_eq!(parse_path(&path_buffer()).unwrap(), None);
+    }
+
+    #[test]
+    fn paths() {
+        let path = parse_path(&buffer(b"/home/user/file name.txt")).unwrap();
+        assert_eq!(path, Some(PathBuf::from("/home/user/file name.txt")));
+
+        let path = parse_path(&buffer("/tmp/ü/🦀.html".as_bytes())).unwrap();
+        assert_eq!(path, Some(PathBuf::from("/tmp/ü/🦀.html")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_path() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = parse_path(&buffer(b"/tmp/\xff\xfe.txt")).unwrap().unwrap();
+        assert_eq!(path.as_os_str().as_bytes(), b"/tmp/\xff\xfe.txt");
+    }
+
+    #[test]
+    fn file_dialog() {
+        let dialog = FileDialog::open()
+            .add_filter("Images", &["*.png", "*.jpg"])
+            .add_filter("All Files", &["*"])
+            .set_multiple(true);
+
+        assert_eq!(dialog.title(), "Open Files");
+        assert_eq!(FileDialog::save().title(), "Save File");
+        assert_eq!(FileDialog::save().set_title("Export").title(), "Export");
+
+        let (names, patterns) = dialog.filter_strings().unwrap();
+        assert_eq!(names[0].to_bytes(), b"Images");
+        assert_eq!(patterns[0].to_bytes(), b"*.png;*.jpg");
+        assert_eq!(names[1].to_bytes(), b"All Files");
+        assert_eq!(patterns[1].to_bytes(), b"*");
+
+        let invalid = FileDialog::open().add_filter("Invalid\0", &["*"]);
+        assert!(invalid.filter_strings().is_err());
+    }
+
+    #[test]
+    fn truncated() {
+        let longest = vec![b'a'; PATH_BUFFER_SIZE - 2];
+        assert!(parse_path(&buffer(&longest)).unwrap().is_some());
+
+        let truncated = vec![b'a'; PATH_BUFFER_SIZE - 1];
+        match parse_path(&buffer(&truncated)) {
+            Err(WebviewError::TruncatedPath) => {}
+            _ => panic!("expected truncated path"),
+        };
+
+        match parse_path(&[b'a'; 8]) {
+            Err(WebviewError::TruncatedPath) => {}
+            _ => panic!("expected truncated path"),
+        };
+    }
+}
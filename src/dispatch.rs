@@ -0,0 +1,438 @@
+//! Module for dispatching closures to the main thread.
+//!
+//! The C library's `webview_dispatch` only forwards a raw pointer to the
+//! callback, so it can not take ownership of a Rust closure. Instead, all
+//! dispatched closures are boxed and stored in a thread safe `DispatchQueue`
+//! owned by the `Webview`. The C library is only used to wake up the main
+//! loop, which then drains the queue and executes every job exactly once.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::mem;
+use std::os::raw::c_uint;
+use std::pin::Pin;
+use std::ptr;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use crate::error::WebviewError;
+use crate::ffi;
+use crate::{Webview, WebviewWrapper};
+use webview_sys as sys;
+
+/// Type alias for a boxed type-erased dispatch job.
+///
+/// The job receives the pointer to the webview struct it has been dispatched
+/// to, which is the first field of both `Webview` and `WebviewWrapper`.
+pub type JobBox = Box<dyn FnOnce(*mut sys::webview) + Send>;
+
+/// Thread safe queue of dispatched jobs
+///
+/// Pushing a job into an empty queue schedules a call to
+/// `callback::dispatch_handler` through the C library, which drains the queue
+/// on the main thread.
+pub struct DispatchQueue {
+    state: Mutex<State>,
+}
+
+struct State {
+    jobs:    VecDeque<JobBox>,
+    webview: *mut sys::webview,
+    /// The scheduled (not yet executed) wake-up of the main loop, if any
+    source:  c_uint,
+    closed:  bool,
+}
+
+/// The webview pointer is only passed to `webview_dispatch`, which is the only
+/// thread safe function of the C library, and only while the queue is open.
+unsafe impl Send for DispatchQueue {}
+unsafe impl Sync for DispatchQueue {}
+
+impl DispatchQueue {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                jobs:    VecDeque::new(),
+                webview: ptr::null_mut(),
+                source:  0,
+                closed:  false,
+            }),
+        }
+    }
+
+    /// Sets the (initialized) webview, which is woken up for all future jobs.
+    ///
+    /// Any jobs pushed before the queue was opened are scheduled immediately.
+    #[inline]
+    pub unsafe fn open(&self, webview: *mut sys::webview) {
+        let mut state = self.state.lock().unwrap();
+        state.webview = webview;
+        if !state.closed && !state.jobs.is_empty() {
+            state.source = ffi::webview_dispatch(webview);
+        }
+    }
+
+    /// Closes the queue, cancels a scheduled wake-up and drops all pending
+    /// jobs.
+    ///
+    /// This must be called before the webview is exited, since a scheduled
+    /// wake-up would otherwise access the exited webview once the main loop
+    /// iterates again (e.g. for another webview). All subsequent attempts to
+    /// push a job will fail.
+    #[inline]
+    pub fn close(&self) {
+        let (jobs, source) = {
+            let mut state = self.state.lock().unwrap();
+            state.closed = true;
+            state.webview = ptr::null_mut();
+            let source = mem::take(&mut state.source);
+            (mem::take(&mut state.jobs), source)
+        };
+
+        unsafe { ffi::webview_dispatch_remove(source) };
+        // jobs are dropped only after the lock has been released
+        drop(jobs);
+    }
+
+    /// Pushes a job to the back of the queue.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `WebviewError::DispatchFailed` if the queue has been closed.
+    #[inline]
+    pub fn push(&self, job: JobBox) -> Result<(), WebviewError> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return Err(WebviewError::DispatchFailed);
+        }
+
+        state.jobs.push_back(job);
+        if state.jobs.len() == 1 && !state.webview.is_null() {
+            state.source = unsafe { ffi::webview_dispatch(state.webview) };
+        }
+
+        Ok(())
+    }
+
+    /// Executes all queued jobs in order, including jobs pushed while
+    /// draining.
+    ///
+    /// The lock is released before each job is executed, so jobs may
+    /// dispatch further jobs themselves.
+    #[inline]
+    pub unsafe fn drain(&self, webview: *mut sys::webview) {
+        // the wake-up is being executed and can no longer be cancelled
+        self.state.lock().unwrap().source = 0;
+        loop {
+            let job = self.state.lock().unwrap().jobs.pop_front();
+            match job {
+                Some(job) => job(webview),
+                None => break,
+            }
+        }
+    }
+}
+
+/// Converts a closure over the webview into a type-erased job.
+#[inline]
+pub fn webview_job<F>(func: F) -> JobBox
+where
+    F: FnOnce(&mut Webview) + Send + 'static,
+{
+    Box::new(move |webview| unsafe { func(&mut *(webview as *mut Webview)) })
+}
+
+/// Converts a closure over the webview and its userdata into a type-erased
+/// job.
+///
+/// # Safety
+///
+/// The job must only be pushed into the queue of a `WebviewWrapper` with the
+/// same userdata type `T`.
+#[inline]
+pub unsafe fn wrapper_job<T, F>(func: F) -> JobBox
+where
+    T: 'static,
+    F: FnOnce(&mut Webview, &mut T) + Send + 'static,
+{
+    Box::new(move |webview| {
+        let wrapper = webview as *mut WebviewWrapper<T>;
+        func(&mut (*wrapper).inner, &mut (*wrapper).ext.userdata);
+    })
+}
+
+/// Creates a connected pair of a `Completer` and a `DispatchHandle`.
+#[inline]
+pub fn completion<R>() -> (Completer<R>, DispatchHandle<R>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(Completion::Pending(None)),
+        cond:  Condvar::new(),
+    });
+
+    (
+        Completer {
+            shared: Arc::clone(&shared),
+        },
+        DispatchHandle { shared },
+    )
+}
+
+struct Shared<R> {
+    state: Mutex<Completion<R>>,
+    cond:  Condvar,
+}
+
+enum Completion<R> {
+    Pending(Option<Waker>),
+    Ready(R),
+    Cancelled,
+    Taken,
+}
+
+impl<R> Shared<R> {
+    #[inline]
+    fn resolve(&self, completion: Completion<R>) {
+        let waker = {
+            let mut state = self.state.lock().unwrap();
+            match mem::replace(&mut *state, completion) {
+                Completion::Pending(waker) => waker,
+                previous => {
+                    // a job can only be resolved once
+                    *state = previous;
+                    return;
+                }
+            }
+        };
+
+        self.cond.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// The sending half of a `DispatchHandle`, owned by the dispatched job
+///
+/// If the job is dropped without having been executed (e.g. because the
+/// webview has been exited before the job could run), the `Completer` cancels
+/// the handle.
+pub struct Completer<R> {
+    shared: Arc<Shared<R>>,
+}
+
+impl<R> Completer<R> {
+    /// Resolves the associated `DispatchHandle` with the given value.
+    #[inline]
+    pub fn complete(self, value: R) {
+        self.shared.resolve(Completion::Ready(value));
+    }
+}
+
+impl<R> Drop for Completer<R> {
+    #[inline]
+    fn drop(&mut self) {
+        self.shared.resolve(Completion::Cancelled);
+    }
+}
+
+/// Handle to the return value of a dispatched function call
+///
+/// The value can either be retrieved by blocking the current thread with
+/// `wait` or by awaiting the handle as a `Future`.
+///
+/// # Errors
+///
+/// Both resolve to a `WebviewError::DispatchFailed` if the job is dropped
+/// before it could be executed, which happens when the webview is terminated
+/// or exited while the job is still queued.
+///
+/// # Notes
+///
+/// Jobs are executed inside a callback of the C library, which can not be
+/// unwound, so a panicking job aborts the process.
+pub struct DispatchHandle<R> {
+    shared: Arc<Shared<R>>,
+}
+
+impl<R> DispatchHandle<R> {
+    /// Blocks the current thread until the dispatched function has been
+    /// executed and returns its return value.
+    ///
+    /// # Notes
+    ///
+    /// This must never be called from the main thread, since the dispatched
+    /// function can only run while the main loop is iterating.
+    #[inline]
+    pub fn wait(self) -> Result<R, WebviewError> {
+        let mut state = self.shared.state.lock().unwrap();
+        loop {
+            match mem::replace(&mut *state, Completion::Taken) {
+                Completion::Pending(waker) => {
+                    *state = Completion::Pending(waker);
+                    state = self.shared.cond.wait(state).unwrap();
+                }
+                Completion::Ready(value) => return Ok(value),
+                Completion::Cancelled => return Err(WebviewError::DispatchFailed),
+                Completion::Taken => unreachable!(),
+            }
+        }
+    }
+
+    /// Returns the return value if the dispatched function has already been
+    /// executed, without blocking.
+    #[inline]
+    pub fn try_wait(&mut self) -> Option<Result<R, WebviewError>> {
+        let mut state = self.shared.state.lock().unwrap();
+        match mem::replace(&mut *state, Completion::Taken) {
+            Completion::Pending(waker) => {
+                *state = Completion::Pending(waker);
+                None
+            }
+            Completion::Ready(value) => Some(Ok(value)),
+            Completion::Cancelled => Some(Err(WebviewError::DispatchFailed)),
+            Completion::Taken => panic!("dispatch result has already been taken"),
+        }
+    }
+}
+
+impl<R> Future for DispatchHandle<R> {
+    type Output = Result<R, WebviewError>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.shared.state.lock().unwrap();
+        match mem::replace(&mut *state, Completion::Taken) {
+            Completion::Pending(_) => {
+                *state = Completion::Pending(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+            Completion::Ready(value) => Poll::Ready(Ok(value)),
+            Completion::Cancelled => Poll::Ready(Err(WebviewError::DispatchFailed)),
+            Completion::Taken => panic!("`DispatchHandle` polled after completion"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::ptr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Barrier};
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread;
+
+    use crate::dispatch::*;
+
+    const THREADS: usize = 16;
+    const JOBS_PER_THREAD: usize = 1_000;
+
+    #[test]
+    fn push_drain_exactly_once() {
+        let queue = Arc::new(DispatchQueue::new());
+        let counters: Arc<Vec<AtomicUsize>> = Arc::new(
+            (0..THREADS * JOBS_PER_THREAD)
+                .map(|_| AtomicUsize::new(0))
+                .collect(),
+        );
+        let barrier = Arc::new(Barrier::new(THREADS + 1));
+        let finished = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..THREADS)
+            .map(|id| {
+                let queue = Arc::clone(&queue);
+                let counters = Arc::clone(&counters);
+                let barrier = Arc::clone(&barrier);
+                let finished = Arc::clone(&finished);
+                thread::spawn(move || {
+                    barrier.wait();
+                    for job in 0..JOBS_PER_THREAD {
+                        let counters = Arc::clone(&counters);
+                        let index = id * JOBS_PER_THREAD + job;
+                        queue
+                            .push(Box::new(move |_| {
+                                counters[index].fetch_add(1, Ordering::SeqCst);
+                            })).unwrap();
+                    }
+                    finished.fetch_add(1, Ordering::SeqCst);
+                })
+            }).collect();
+
+        // drain concurrently while the threads are still pushing
+        barrier.wait();
+        while finished.load(Ordering::SeqCst) < THREADS {
+            unsafe { queue.drain(ptr::null_mut()) };
+            thread::yield_now();
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        unsafe { queue.drain(ptr::null_mut()) };
+
+        assert!(counters.iter().all(|c| c.load(Ordering::SeqCst) == 1));
+    }
+
+    #[test]
+    fn push_after_close() {
+        let queue = DispatchQueue::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        let pending = Arc::clone(&counter);
+        queue.push(Box::new(move |_| {
+            pending.fetch_add(1, Ordering::SeqCst);
+        })).unwrap();
+        queue.close();
+
+        assert_eq!(Arc::strong_count(&counter), 1, "pending job not dropped");
+        assert!(queue.push(Box::new(|_| {})).is_err());
+        unsafe { queue.drain(ptr::null_mut()) };
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn completion_wait() {
+        let (completer, handle) = completion();
+        thread::spawn(move || completer.complete(42));
+        assert_eq!(handle.wait().unwrap(), 42);
+    }
+
+    #[test]
+    fn completion_cancelled_on_close() {
+        let queue = DispatchQueue::new();
+        let (completer, handle) = completion::<()>();
+        queue
+            .push(Box::new(move |_| completer.complete(())))
+            .unwrap();
+        queue.close();
+
+        assert!(handle.wait().is_err());
+    }
+
+    #[test]
+    fn completion_future() {
+        struct ThreadWaker(thread::Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let (completer, mut handle) = completion();
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        assert!(Pin::new(&mut handle).poll(&mut cx).is_pending());
+        thread::spawn(move || completer.complete("done"));
+
+        loop {
+            match Pin::new(&mut handle).poll(&mut cx) {
+                Poll::Ready(result) => break assert_eq!(result.unwrap(), "done"),
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Module for self-contained HTML documents with inlined styles and scripts.
+//!
+//! An `HtmlDocument` combines an HTML shell with stylesheets and scripts into
+//! a single document, so no relative assets are required when the document
+//! is loaded as `Content::Html`.
+
+use std::borrow::Cow;
+
+use crate::content::Content;
+
+/// An HTML document with inlined stylesheets and scripts
+///
+/// Stylesheets are inserted at the end of the `<head>` element and scripts at
+/// the end of the `<body>` element, both in the order they were added. Any
+/// closing `</style>` or `</script>` tags in the sources are escaped, so they
+/// can not terminate the surrounding element prematurely.
+///
+/// # Examples
+///
+/// ```ignore
+/// let document = HtmlDocument::new(include_str!("index.html"))
+///     .add_style(include_str!("styles.css"))
+///     .add_script(include_str!("app.js"));
+///
+/// let webview = Builder::without_userdata()
+///     .set_title("Example")
+///     .set_content(document)
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct HtmlDocument {
+    html:    String,
+    styles:  Vec<String>,
+    scripts: Vec<String>,
+}
+
+impl HtmlDocument {
+    #[inline]
+    pub fn new(html: impl Into<String>) -> Self {
+        Self {
+            html:    html.into(),
+            styles:  Vec::new(),
+            scripts: Vec::new(),
+        }
+    }
+
+    /// Adds a stylesheet, which is inlined in a `<style>` element.
+    #[inline]
+    pub fn add_style<'css>(mut self, css: impl Into<Cow<'css, str>>) -> Self {
+        self.styles.push(escape_closing_tag(&css.into(), "style"));
+        self
+    }
+
+    /// Adds a script, which is inlined in a `<script>` element.
+    ///
+    /// Occurrences of `</script` and `<!--` are escaped as `<\/script` and
+    /// `<\!--`, which is only equivalent within string literals, regular
+    /// expressions and comments.
+    #[inline]
+    pub fn add_script<'js>(mut self, js: impl Into<Cow<'js, str>>) -> Self {
+        let js = escape_closing_tag(&js.into(), "script");
+        self.scripts.push(js.replace("<!--", "<\\!--"));
+        self
+    }
+
+    /// Renders the document with all stylesheets and scripts inlined.
+    #[inline]
+    pub fn render(&self) -> String {
+        let mut styles = String::new();
+        for css in &self.styles {
+            styles.push_str("<style>\n");
+            styles.push_str(css);
+            styles.push_str("\n</style>\n");
+        }
+
+        let mut scripts = String::new();
+        for js in &self.scripts {
+            scripts.push_str("<script>\n");
+            scripts.push_str(js);
+            scripts.push_str("\n</script>\n");
+        }
+
+        let mut html = self.html.clone();
+
+        let idx = find_tag(&html, "</head", false)
+            .or_else(|| find_tag(&html, "<body", false))
+            .or_else(|| find_tag(&html, "<head", false).and_then(|idx| tag_end(&html, idx)))
+            .unwrap_or(0);
+        html.insert_str(idx, &styles);
+
+        let idx = find_tag(&html, "</body", true)
+            .or_else(|| find_tag(&html, "</html", true))
+            .unwrap_or(html.len());
+        html.insert_str(idx, &scripts);
+
+        html
+    }
+}
+
+impl<'content> From<HtmlDocument> for Content<'content, String> {
+    #[inline]
+    fn from(document: HtmlDocument) -> Self {
+        Content::Html(document.render())
+    }
+}
+
+/// Escapes all (case-insensitive) occurrences of `</tag` as `<\/tag`.
+fn escape_closing_tag(source: &str, tag: &str) -> String {
+    let mut escaped = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(idx) = find_tag(rest, &format!("</{}", tag), false) {
+        escaped.push_str(&rest[..idx]);
+        escaped.push_str("<\\/");
+        rest = &rest[idx + 2..];
+    }
+
+    escaped.push_str(rest);
+    escaped
+}
+
+/// Finds the first (or last) case-insensitive occurrence of a tag.
+///
+/// The tag has to be followed by `>`, whitespace, `/` or the end of the
+/// string, so e.g. `<head` does not match `<header>`.
+fn find_tag(html: &str, tag: &str, last: bool) -> Option<usize> {
+    let lowercase = html.to_ascii_lowercase();
+    let is_boundary = |idx: usize| {
+        lowercase[idx + tag.len()..]
+            .chars()
+            .next()
+            .is_none_or(|ch| ch == '>' || ch == '/' || ch.is_ascii_whitespace())
+    };
+
+    let mut matches = lowercase.match_indices(tag).map(|(idx, _)| idx);
+    if last {
+        matches.filter(|&idx| is_boundary(idx)).last()
+    } else {
+        matches.find(|&idx| is_boundary(idx))
+    }
+}
+
+/// Returns the index after the closing `>` of the tag starting at `idx`.
+fn tag_end(html: &str, idx: usize) -> Option<usize> {
+    html[idx..].find('>').map(|end| idx + end + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+
+    use crate::content::Content;
+    use crate::document::*;
+
+    #[test]
+    fn inline() {
+        let document = HtmlDocument::new(
+            "<html><HEAD><title>Test</title></HEAD><body><header></header><p>Text</p></Body></html>",
+        ).add_style("p { color: red; }")
+        .add_script("init();")
+        .add_script("start();");
+
+        assert_eq!(
+            document.render(),
+            "<html><HEAD><title>Test</title><style>\np { color: red; }\n</style>\n</HEAD>\
+             <body><header></header><p>Text</p><script>\ninit();\n</script>\n\
+             <script>\nstart();\n</script>\n</Body></html>"
+        );
+    }
+
+    #[test]
+    fn fragment() {
+        let document = HtmlDocument::new("<p>Text</p>")
+            .add_style("p {}")
+            .add_script("init();");
+
+        assert_eq!(
+            document.render(),
+            "<style>\np {}\n</style>\n<p>Text</p><script>\ninit();\n</script>\n"
+        );
+    }
+
+    #[test]
+    fn escape() {
+        let document = HtmlDocument::new("<body></body>")
+            .add_style("p::after { content: \"</STYLE>\"; }")
+            .add_script("var tag = '</script>' + \"</Script \" + '<!-- x';");
+
+        let html = document.render();
+        assert!(html.contains("content: \"<\\/STYLE>\""));
+        assert!(html.contains("var tag = '<\\/script>' + \"<\\/Script \" + '<\\!-- x';"));
+        assert_eq!(html.matches("</style>").count(), 1);
+        assert_eq!(html.to_ascii_lowercase().matches("</script").count(), 1);
+    }
+
+    #[test]
+    fn into_content() {
+        let content: Content<String> = HtmlDocument::new("<p>Text</p>").add_style("p {}").into();
+        let url: Cow<str> = content.into_url();
+        assert!(url.starts_with("data:text/html;charset=utf-8,%3Cstyle%3E"));
+    }
+}
@@ -1,6 +1,7 @@
 use std::error::Error;
 use std::ffi::{FromBytesWithNulError, NulError};
 use std::fmt;
+use std::path::PathBuf;
 
 use self::WebviewError::*;
 use crate::conversion::CStrConversionError;
@@ -10,10 +11,18 @@ use crate::ffi::LibraryError;
 pub enum WebviewError {
     Build,
     DispatchFailed,
+    DuplicateScheme(String),
     Library(LibraryError),
-    InvalidPath,
+    InvalidIcon,
+    InvalidPath(PathBuf),
+    InvalidSize(usize, usize),
     InvalidStr(CStrConversionError),
+    InvalidUrl(String),
     InvalidThread,
+    TruncatedPath,
+    Unsupported(&'static str),
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
 }
 
 impl fmt::Display for WebviewError {
@@ -21,14 +30,22 @@ impl fmt::Display for WebviewError {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match *self {
             Build => write!(f, "failed to to build webview due to missing required arguments"),
-            DispatchFailed => write!(f, "failed to dispatch callback from thread (main thread handle no longer exists)"),
+            DispatchFailed => write!(f, "failed to dispatch callback from thread (main thread handle no longer exists or webview has been terminated)"),
+            DuplicateScheme(ref scheme) => write!(f, "URI scheme already registered: {}", scheme),
             Library(ref err) => write!(f, "webview C library: {}", err.description()),
-            InvalidPath => unimplemented!(), //TODO: Write error message
+            InvalidIcon => write!(f, "failed to decode window icon image data"),
+            InvalidPath(ref path) => write!(f, "invalid or non-existent file path: {}", path.display()),
+            InvalidSize(width, height) => write!(f, "invalid window size: {}x{}", width, height),
             InvalidStr(ref err) => write!(f, "string conversion error: {}", err.description()),
+            InvalidUrl(ref url) => write!(f, "invalid or relative URL: {}", url),
             InvalidThread => write!(
                 f, "failed to start webview: Attempt to run on thread other than `main` \
                 (check can be disabled by calling `Builder::deactivate_thread_check`)"
             ),
+            TruncatedPath => write!(f, "path returned by dialog exceeds the maximum path length"),
+            Unsupported(feature) => write!(f, "{} not supported on this platform", feature),
+            #[cfg(feature = "serde")]
+            Json(ref err) => write!(f, "failed to serialize argument: {}", err),
         }
     }
 }
@@ -49,6 +66,14 @@ impl From<CStrConversionError> for WebviewError {
     }
 }
 
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for WebviewError {
+    #[inline]
+    fn from(error: serde_json::Error) -> Self {
+        WebviewError::Json(error)
+    }
+}
+
 impl From<FromBytesWithNulError> for WebviewError {
     #[inline]
     fn from(error: FromBytesWithNulError) -> Self {
@@ -1,13 +1,18 @@
 use std::borrow::Cow;
 use std::ffi::{CStr, CString};
 use std::fmt::Write;
+#[cfg(feature = "serde")]
+use std::io;
 
-/// Possible JS argument types (simplified)
-pub enum Arg<'s> {
-    Int(usize),
-    Float(f64),
-    Str(&'s str),
-}
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde_json::ser::{Formatter, Serializer};
+
+use crate::value::JsValue;
+
+/// Type alias for arguments of `Webview::eval_fn`
+pub type Arg<'s> = JsValue<'s>;
 
 #[derive(Debug, Clone)]
 pub struct StringBuffers {
@@ -38,12 +43,17 @@ pub struct EvalBuffer {
 
 impl EvalBuffer {
     #[inline]
-    fn new(buffer_size: usize) -> Self {
+    pub fn new(buffer_size: usize) -> Self {
         Self {
             inner: String::with_capacity(buffer_size),
         }
     }
 
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.inner
+    }
+
     #[inline]
     pub fn push_str(&mut self, string: &str) {
         self.inner.push_str(string);
@@ -54,15 +64,105 @@ impl EvalBuffer {
         self.inner.push(ch);
     }
 
+    /// Serializes a (possibly nested) JS value into JS source code.
     #[inline]
     pub fn push_arg<'s>(&mut self, arg: &Arg<'s>) {
-        match arg {
-            Arg::Int(val) => write!(&mut self.inner, "{}", val).unwrap(),
-            Arg::Float(val) => write!(&mut self.inner, "{}", val).unwrap(),
-            Arg::Str(ref string) => write!(&mut self.inner, "'{}'", string).unwrap(),
+        match *arg {
+            JsValue::Null => self.inner.push_str("null"),
+            JsValue::Bool(val) => self.inner.push_str(if val { "true" } else { "false" }),
+            JsValue::Int(val) => write!(&mut self.inner, "{}", val).unwrap(),
+            JsValue::UInt(val) => write!(&mut self.inner, "{}", val).unwrap(),
+            JsValue::Float(val) => self.push_float(val),
+            JsValue::Str(ref string) => self.push_js_string(string),
+            JsValue::Array(ref values) => {
+                self.inner.push('[');
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        self.inner.push(',');
+                    }
+                    self.push_arg(value);
+                }
+                self.inner.push(']');
+            }
+            JsValue::Object(ref entries) => {
+                self.inner.push('{');
+                for (idx, (key, value)) in entries.iter().enumerate() {
+                    if idx > 0 {
+                        self.inner.push(',');
+                    }
+                    self.push_js_string(key);
+                    self.inner.push(':');
+                    self.push_arg(value);
+                }
+                self.inner.push('}');
+            }
         };
     }
 
+    #[inline]
+    fn push_float(&mut self, val: f64) {
+        if val.is_nan() {
+            self.inner.push_str("NaN");
+        } else if val.is_infinite() {
+            self.inner.push_str(if val > 0.0 { "Infinity" } else { "-Infinity" });
+        } else {
+            write!(&mut self.inner, "{:?}", val).unwrap();
+        }
+    }
+
+    /// Pushes a string as an escaped JS string literal (including quotes).
+    ///
+    /// Besides quotes, backslashes and control characters, the line
+    /// terminators U+2028 and U+2029 as well as `<` and `>` are escaped, so
+    /// the literal can neither break the surrounding code nor close an
+    /// enclosing HTML `<script>` element.
+    #[inline]
+    pub fn push_js_string(&mut self, string: &str) {
+        self.inner.reserve(string.len() + 2);
+        self.inner.push('\'');
+        for ch in string.chars() {
+            match ch {
+                '\'' => self.inner.push_str("\\'"),
+                '"' => self.inner.push_str("\\\""),
+                '\\' => self.inner.push_str("\\\\"),
+                '\n' => self.inner.push_str("\\n"),
+                '\r' => self.inner.push_str("\\r"),
+                '\t' => self.inner.push_str("\\t"),
+                '\0'..='\u{1f}' | '\u{7f}' | '<' | '>' | '\u{2028}' | '\u{2029}' => {
+                    write!(&mut self.inner, "\\u{:04X}", ch as u32).unwrap()
+                }
+                _ => self.inner.push(ch),
+            }
+        }
+        self.inner.push('\'');
+    }
+
+    /// Serializes any `Serialize` type as JSON directly into the buffer.
+    ///
+    /// JSON strings are escaped just like with `push_js_string`.
+    /// Non-finite floating point numbers are serialized as `null`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value can not be represented as JSON (e.g. maps with
+    /// non-string keys), in which case the buffer is left unchanged.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub fn push_json<S>(&mut self, value: &S) -> Result<(), serde_json::Error>
+    where
+        S: Serialize + ?Sized,
+    {
+        let len = self.inner.len();
+        // serde_json only ever writes complete UTF-8 encoded string fragments
+        let bytes = unsafe { self.inner.as_mut_vec() };
+        let result = value.serialize(&mut Serializer::with_formatter(&mut *bytes, JsFormatter));
+        if result.is_err() {
+            bytes.truncate(len);
+        }
+
+        result
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         self.inner.clear();
@@ -77,3 +177,224 @@ impl EvalBuffer {
         self.inner.as_bytes()
     }
 }
+
+/// Object safe version of `Serialize` for passing arbitrary types to
+/// `Webview::eval_fn_serde`
+///
+/// This is implemented for all types implementing `Serialize`.
+#[cfg(feature = "serde")]
+pub trait JsonArg {
+    fn push_json(&self, buffer: &mut EvalBuffer) -> Result<(), serde_json::Error>;
+}
+
+#[cfg(feature = "serde")]
+impl<S> JsonArg for S
+where
+    S: Serialize + ?Sized,
+{
+    #[inline]
+    fn push_json(&self, buffer: &mut EvalBuffer) -> Result<(), serde_json::Error> {
+        buffer.push_json(self)
+    }
+}
+
+/// JSON formatter additionally escaping characters, which are valid in JSON
+/// strings but not (or not safely) in JS string literals
+#[cfg(feature = "serde")]
+struct JsFormatter;
+
+#[cfg(feature = "serde")]
+impl Formatter for JsFormatter {
+    #[inline]
+    fn write_string_fragment<W>(&mut self, writer: &mut W, fragment: &str) -> io::Result<()>
+    where
+        W: io::Write + ?Sized,
+    {
+        let mut start = 0;
+        for (idx, ch) in fragment.char_indices() {
+            let escaped = match ch {
+                '<' => "\\u003C",
+                '>' => "\\u003E",
+                '\u{2028}' => "\\u2028",
+                '\u{2029}' => "\\u2029",
+                _ => continue,
+            };
+
+            writer.write_all(&fragment.as_bytes()[start..idx])?;
+            writer.write_all(escaped.as_bytes())?;
+            start = idx + ch.len_utf8();
+        }
+
+        writer.write_all(&fragment.as_bytes()[start..])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::f64;
+
+    use crate::eval::*;
+
+    fn serialize(arg: Arg) -> String {
+        let mut buffer = EvalBuffer::new(0);
+        buffer.push_arg(&arg);
+        buffer.inner
+    }
+
+    #[test]
+    fn primitives() {
+        assert_eq!(serialize(Arg::Null), "null");
+        assert_eq!(serialize(Arg::from(())), "null");
+        assert_eq!(serialize(Arg::from(true)), "true");
+        assert_eq!(serialize(Arg::from(false)), "false");
+        assert_eq!(serialize(Arg::from(-42i32)), "-42");
+        assert_eq!(serialize(Arg::from(u64::MAX)), "18446744073709551615");
+        assert_eq!(serialize(Arg::from(None::<i32>)), "null");
+        assert_eq!(serialize(Arg::from(Some(1u8))), "1");
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(serialize(Arg::from(1.5)), "1.5");
+        assert_eq!(serialize(Arg::from(-2.0f32)), "-2.0");
+        assert_eq!(serialize(Arg::from(1e300)), "1e300");
+        assert_eq!(serialize(Arg::from(f64::NAN)), "NaN");
+        assert_eq!(serialize(Arg::from(f64::INFINITY)), "Infinity");
+        assert_eq!(serialize(Arg::from(f64::NEG_INFINITY)), "-Infinity");
+    }
+
+    #[test]
+    fn nested() {
+        let mut map = BTreeMap::new();
+        map.insert("a", Arg::from(vec![1, 2, 3]));
+        map.insert("b", Arg::object(vec![("c", Arg::Null), ("d", Arg::from(true))]));
+
+        assert_eq!(serialize(Arg::from(map)), "{'a':[1,2,3],'b':{'c':null,'d':true}}");
+        assert_eq!(serialize(Arg::from(&[-1i64, 0][..])), "[-1,0]");
+        assert_eq!(serialize(Arg::Array(vec![])), "[]");
+        assert_eq!(serialize(Arg::Object(vec![])), "{}");
+    }
+
+    #[test]
+    fn escaped_strings() {
+        assert_eq!(serialize(Arg::from("it's")), r"'it\'s'");
+        assert_eq!(serialize(Arg::from(r#"a\"b"#)), r#"'a\\\"b'"#);
+        assert_eq!(serialize(Arg::from("a\nb\r\t")), r"'a\nb\r\t'");
+        assert_eq!(serialize(Arg::from("\u{2028}\u{2029}")), r"'\u2028\u2029'");
+        assert_eq!(serialize(Arg::from("</script>")), r"'\u003C/script\u003E'");
+        assert_eq!(serialize(Arg::from("\0\u{1b}")), r"'\u0000\u001B'");
+        assert_eq!(serialize(Arg::from("ünï©ødé 🦀")), "'ünï©ødé 🦀'");
+    }
+
+    /// Minimal xorshift PRNG, so no external dependencies are required
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn next_char(&mut self) -> char {
+            const SPECIAL: &[char] = &[
+                '\'', '"', '\\', '\n', '\r', '\t', '\0', '<', '>', '/', '\u{7f}', '\u{2028}',
+                '\u{2029}', '\u{feff}', '\u{10ffff}',
+            ];
+
+            match self.next() % 4 {
+                0 => SPECIAL[(self.next() % SPECIAL.len() as u64) as usize],
+                1 => (self.next() % 0x80) as u8 as char,
+                _ => loop {
+                    if let Some(ch) = std::char::from_u32((self.next() % 0x11_0000) as u32) {
+                        break ch;
+                    }
+                },
+            }
+        }
+    }
+
+    /// Parses a single JS string literal according to the ECMAScript
+    /// specification and returns the string value.
+    fn parse_js_string(literal: &str) -> Result<String, String> {
+        let mut chars = literal.chars();
+        let quote = match chars.next() {
+            Some(quote @ '\'') | Some(quote @ '"') => quote,
+            other => return Err(format!("invalid opening quote: {:?}", other)),
+        };
+
+        let mut units: Vec<u16> = Vec::new();
+        let mut buf = [0u16; 2];
+        loop {
+            let ch = chars.next().ok_or("unterminated literal")?;
+            match ch {
+                '\n' | '\r' | '\u{2028}' | '\u{2029}' => {
+                    return Err(format!("unescaped line terminator {:?}", ch))
+                }
+                '\\' => {
+                    let escaped = chars.next().ok_or("unterminated escape")?;
+                    match escaped {
+                        'b' => units.push(0x08),
+                        'f' => units.push(0x0c),
+                        'n' => units.push(0x0a),
+                        'r' => units.push(0x0d),
+                        't' => units.push(0x09),
+                        'v' => units.push(0x0b),
+                        '0' => units.push(0x00),
+                        'x' => {
+                            let hex: String = chars.by_ref().take(2).collect();
+                            units.push(u16::from_str_radix(&hex, 16).map_err(|e| e.to_string())?);
+                        }
+                        'u' => {
+                            let hex: String = chars.by_ref().take(4).collect();
+                            units.push(u16::from_str_radix(&hex, 16).map_err(|e| e.to_string())?);
+                        }
+                        other => units.extend_from_slice(other.encode_utf16(&mut buf)),
+                    }
+                }
+                ch if ch == quote => break,
+                ch => units.extend_from_slice(ch.encode_utf16(&mut buf)),
+            }
+        }
+
+        if chars.next().is_some() {
+            return Err("trailing characters after literal".into());
+        }
+
+        String::from_utf16(&units).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn escape_round_trip() {
+        let mut rng = XorShift(0x2545_F491_4F6C_DD1D);
+        let mut buffer = EvalBuffer::new(0);
+
+        for _ in 0..10_000 {
+            let len = (rng.next() % 32) as usize;
+            let string: String = (0..len).map(|_| rng.next_char()).collect();
+
+            buffer.clear();
+            buffer.push_js_string(&string);
+
+            assert!(!buffer.inner.contains('<') && !buffer.inner.contains('\0'));
+            assert_eq!(parse_js_string(&buffer.inner), Ok(string));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json() {
+        let mut buffer = EvalBuffer::new(0);
+        buffer
+            .push_json(&("</script>\u{2028}", vec![1, 2], None::<()>, 0.5))
+            .unwrap();
+        assert_eq!(buffer.inner, r#"["\u003C/script\u003E\u2028",[1,2],null,0.5]"#);
+
+        let mut invalid = BTreeMap::new();
+        invalid.insert(vec![0], 0);
+        assert!(buffer.push_json(&invalid).is_err());
+        assert_eq!(buffer.inner, r#"["\u003C/script\u003E\u2028",[1,2],null,0.5]"#);
+    }
+}
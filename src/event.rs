@@ -0,0 +1,28 @@
+//! Module for emitting named events from Rust to Javascript.
+//!
+//! The injected shim defines `window.rust.on(event, handler)` and
+//! `window.rust.off(event, [handler])` for subscribing to events, which are
+//! emitted by evaluating `window.rust.__emit(event, payload)`.
+
+use crate::value::JsValue;
+use crate::Webview;
+
+/// The Javascript function evaluated for emitting events
+pub const EMIT_FN: &str = "window.rust.__emit";
+
+/// Javascript shim defining the event API, injected into every page by
+/// `Builder::build`
+pub const JS_SHIM: &str = "(function(){var rust=window.rust=window.rust||{};var listeners={};\
+     rust.on=function(event,handler){(listeners[event]=listeners[event]||[]).push(handler);};\
+     rust.off=function(event,handler){var l=listeners[event];if(!l){return;}\
+     if(handler===undefined){delete listeners[event];return;}\
+     var i=l.indexOf(handler);if(i>=0){l.splice(i,1);}};\
+     rust.__emit=function(event,payload){var l=listeners[event];\
+     if(l){l.slice().forEach(function(handler){handler(payload);});}};})();";
+
+/// Emits an event by evaluating the emit function with event name and
+/// payload.
+#[inline]
+pub fn emit(webview: &mut Webview, event: &str, payload: JsValue) -> crate::Result {
+    webview.eval_fn(EMIT_FN, &[JsValue::from(event), payload])
+}
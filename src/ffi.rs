@@ -1,20 +1,33 @@
 //! TODO: ffi mod doc
 
 use std::borrow::Cow;
+use std::cmp;
+use std::convert::TryFrom;
 use std::error;
 use std::ffi::CStr;
 use std::fmt;
-use std::mem;
-use std::os::raw::{c_char, c_int, c_void};
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use std::path::PathBuf;
+use std::ptr;
+use std::time::Duration;
 
+use crate::assets::ASSETS_SCHEME;
 use crate::callback;
 use crate::conversion::convert_to_cstring;
+use crate::dialog::{self, FileDialog, FileDialogKind};
 use crate::error::WebviewError;
-use crate::Webview;
+use crate::user_content::{UserScript, UserStyle};
+use crate::window::WindowState;
 use webview_sys as sys;
 
 type DispatchFn = sys::c_webview_dispatch_fn;
 type InvokeFn = sys::c_extern_callback_fn;
+type TimerFn = sys::c_webview_timer_fn;
+type LifecycleFn = sys::c_webview_lifecycle_fn;
+type PolicyFn = sys::c_webview_policy_fn;
+type SchemeFn = sys::c_webview_scheme_fn;
+type WindowStateFn = sys::c_webview_window_state_fn;
+type FileFn = sys::c_webview_file_fn;
 
 /// Dialog options
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq)]
@@ -253,15 +266,505 @@ pub unsafe fn webview_dialog<'title, 'arg>(
 }
 
 #[inline]
-pub unsafe fn webview_dispatch<'invoke, T>(
+pub unsafe fn webview_dialog_save<'title, 'name>(
     webview: &mut sys::webview,
-    func: &dyn FnMut(&mut Webview, &mut T),
-) {
-    let callback: *mut c_void = mem::transmute(&func);
-    sys::webview_dispatch(
+    title: impl Into<Cow<'title, str>>,
+    name: impl Into<Cow<'name, str>>,
+    result_buffer: &mut [u8],
+) -> Result<(), WebviewError> {
+    let title_cstr = convert_to_cstring(title)?;
+    let name_cstr = convert_to_cstring(name)?;
+    let (ptr, size) = (result_buffer.as_mut_ptr(), result_buffer.len());
+
+    sys::webview_dialog_save(
+        webview as *mut _,
+        title_cstr.as_ptr(),
+        name_cstr.as_ptr(),
+        ptr as *mut c_char,
+        size,
+    );
+    Ok(())
+}
+
+/// Shows a file dialog and returns the selected paths, which are collected
+/// by `callback::file_dialog_handler`
+#[inline]
+pub unsafe fn webview_file_dialog(
+    webview: &mut sys::webview,
+    file_dialog: &FileDialog,
+) -> Result<Vec<PathBuf>, WebviewError> {
+    let title_cstr = convert_to_cstring(file_dialog.title())?;
+    let directory_cstr = match file_dialog.directory {
+        Some(ref directory) => Some(dialog::path_to_cstring(directory)?),
+        None => None,
+    };
+    let name_cstr = match file_dialog.file_name {
+        Some(ref file_name) if file_dialog.kind == FileDialogKind::Save => {
+            Some(convert_to_cstring(file_name.as_str())?)
+        }
+        _ => None,
+    };
+
+    let (names, patterns) = file_dialog.filter_strings()?;
+    let name_ptrs: Vec<*const c_char> = names.iter().map(|name| name.as_ptr()).collect();
+    let pattern_ptrs: Vec<*const c_char> =
+        patterns.iter().map(|pattern| pattern.as_ptr()).collect();
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let result = sys::webview_file_dialog(
+        webview as *mut _,
+        file_dialog.kind.as_raw(),
+        title_cstr.as_ptr(),
+        directory_cstr
+            .as_ref()
+            .map_or(ptr::null(), |cstr| cstr.as_ptr()),
+        name_cstr.as_ref().map_or(ptr::null(), |cstr| cstr.as_ptr()),
+        file_dialog.multiple as c_int,
+        file_dialog.confirm_overwrite as c_int,
+        name_ptrs.as_ptr(),
+        pattern_ptrs.as_ptr(),
+        name_ptrs.len(),
+        Some(callback::file_dialog_handler as FileFn),
+        &mut paths as *mut Vec<PathBuf> as *mut c_void,
+    );
+
+    match result {
+        -1 => Err(WebviewError::Unsupported("file dialogs")),
+        _ => Ok(paths),
+    }
+}
+
+/// Schedules `callback::dispatch_handler` for execution on the main thread
+/// and returns the ID of the scheduled source (0 if it can not be cancelled)
+///
+/// This is the only function of the C library which may be called from any
+/// thread, hence the raw pointer.
+/// The dispatched closures themselves are owned by the `DispatchQueue`.
+#[inline]
+pub unsafe fn webview_dispatch(webview: *mut sys::webview) -> c_uint {
+    sys::webview_dispatch_add(
+        webview,
+        Some(callback::dispatch_handler as DispatchFn),
+        ptr::null_mut(),
+    )
+}
+
+/// Cancels a scheduled call of `callback::dispatch_handler`
+#[inline]
+pub unsafe fn webview_dispatch_remove(source: c_uint) {
+    if source != 0 {
+        sys::webview_dispatch_remove(source);
+    }
+}
+
+/// Adds a main loop timer calling `callback::timer_handler` with the given
+/// timer ID after each interval
+///
+/// The interval is rounded down to milliseconds.
+#[inline]
+pub unsafe fn webview_timer_add(
+    webview: *mut sys::webview,
+    interval: Duration,
+    id: u64,
+) -> Result<c_uint, WebviewError> {
+    let millis = interval
+        .as_secs()
+        .saturating_mul(1000)
+        .saturating_add(u64::from(interval.subsec_millis()));
+    let millis = cmp::min(millis, u64::from(c_uint::MAX)) as c_uint;
+
+    let source = sys::webview_timer_add(
+        webview,
+        millis,
+        Some(callback::timer_handler as TimerFn),
+        id as usize as *mut c_void,
+    );
+
+    match source {
+        0 => Err(WebviewError::Unsupported("timers")),
+        source => Ok(source),
+    }
+}
+
+#[inline]
+pub unsafe fn webview_timer_remove(source: c_uint) {
+    sys::webview_timer_remove(source);
+}
+
+/// Connects the lifecycle hooks of the `WebviewWrapper` with userdata type `T`
+#[inline]
+pub unsafe fn webview_lifecycle_connect<T>(webview: *mut sys::webview) {
+    sys::webview_lifecycle_connect(
+        webview,
+        Some(callback::lifecycle_handler::<T> as LifecycleFn),
+    );
+}
+
+/// Connects the navigation handler of the `WebviewWrapper` with userdata type
+/// `T`
+#[inline]
+pub unsafe fn webview_policy_connect<T>(webview: *mut sys::webview) {
+    sys::webview_policy_connect(webview, Some(callback::navigation_handler::<T> as PolicyFn));
+}
+
+/// Registers a custom URI scheme answered by the scheme handlers of the
+/// `WebviewWrapper` with userdata type `T`
+#[inline]
+pub unsafe fn webview_scheme_register<T>(
+    webview: *mut sys::webview,
+    scheme: &str,
+) -> Result<(), WebviewError> {
+    let scheme_cstr = convert_to_cstring(scheme)?;
+    let result = sys::webview_scheme_register(
+        webview,
+        scheme_cstr.as_ptr(),
+        Some(callback::scheme_handler::<T> as SchemeFn),
+    );
+
+    scheme_result(result, scheme)
+}
+
+/// Registers the internal scheme serving the `Assets` of the webview
+#[inline]
+pub unsafe fn webview_assets_register(webview: *mut sys::webview) -> Result<(), WebviewError> {
+    let scheme_cstr = convert_to_cstring(ASSETS_SCHEME)?;
+    let result = sys::webview_scheme_register(
+        webview,
+        scheme_cstr.as_ptr(),
+        Some(callback::assets_handler as SchemeFn),
+    );
+
+    scheme_result(result, ASSETS_SCHEME)
+}
+
+/// Removes all custom URI schemes registered for the webview, which must be
+/// done before the webview is exited
+#[inline]
+pub unsafe fn webview_scheme_unregister_all(webview: *mut sys::webview) {
+    sys::webview_scheme_unregister_all(webview);
+}
+
+/// Converts the result of `webview_scheme_register`
+#[inline]
+fn scheme_result(result: c_int, scheme: &str) -> Result<(), WebviewError> {
+    match result {
+        0 => Ok(()),
+        -2 => Err(WebviewError::DuplicateScheme(scheme.to_owned())),
+        _ => Err(WebviewError::Unsupported("custom URI schemes")),
+    }
+}
+
+/// Answers a custom scheme request, the data is copied by the C library
+#[inline]
+pub unsafe fn webview_scheme_finish(
+    request: *mut c_void,
+    data: &[u8],
+    mime_type: &str,
+) -> Result<(), WebviewError> {
+    let mime_cstr = convert_to_cstring(mime_type)?;
+    sys::webview_scheme_finish(
+        request,
+        data.as_ptr() as *const c_void,
+        data.len(),
+        mime_cstr.as_ptr(),
+    );
+    Ok(())
+}
+
+#[inline]
+pub unsafe fn webview_scheme_fail(request: *mut c_void, message: &str) {
+    let message_cstr = convert_to_cstring(message).expect("invalid error message");
+    sys::webview_scheme_fail(request, message_cstr.as_ptr());
+}
+
+/// Converts the result of a C navigation function, which is `false` if there
+/// was no history entry to navigate to
+#[inline]
+fn navigation_result(result: c_int) -> Result<bool, WebviewError> {
+    match result {
+        -1 => Err(WebviewError::Unsupported("runtime navigation")),
+        0 => Ok(false),
+        _ => Ok(true),
+    }
+}
+
+#[inline]
+pub unsafe fn webview_navigate<'url>(
+    webview: &mut sys::webview,
+    url: impl Into<Cow<'url, str>>,
+) -> Result<(), WebviewError> {
+    let url_cstr = convert_to_cstring(url)?;
+    navigation_result(sys::webview_navigate(webview as *mut _, url_cstr.as_ptr()))?;
+    Ok(())
+}
+
+#[inline]
+pub unsafe fn webview_load_html<'html, 'base>(
+    webview: &mut sys::webview,
+    html: impl Into<Cow<'html, str>>,
+    base_uri: Option<impl Into<Cow<'base, str>>>,
+) -> Result<(), WebviewError> {
+    let html_cstr = convert_to_cstring(html)?;
+    let base_cstr = match base_uri {
+        Some(base_uri) => Some(convert_to_cstring(base_uri)?),
+        None => None,
+    };
+    let base_ptr = base_cstr.as_ref().map_or(ptr::null(), |cstr| cstr.as_ptr());
+
+    let result = sys::webview_load_html(webview as *mut _, html_cstr.as_ptr(), base_ptr);
+    navigation_result(result)?;
+    Ok(())
+}
+
+#[inline]
+pub unsafe fn webview_reload(webview: &mut sys::webview) -> Result<(), WebviewError> {
+    navigation_result(sys::webview_reload(webview as *mut _))?;
+    Ok(())
+}
+
+#[inline]
+pub unsafe fn webview_go_back(webview: &mut sys::webview) -> Result<bool, WebviewError> {
+    navigation_result(sys::webview_go_back(webview as *mut _))
+}
+
+#[inline]
+pub unsafe fn webview_go_forward(webview: &mut sys::webview) -> Result<bool, WebviewError> {
+    navigation_result(sys::webview_go_forward(webview as *mut _))
+}
+
+#[inline]
+pub unsafe fn webview_user_script_add(
+    webview: &mut sys::webview,
+    script: &UserScript,
+) -> Result<(), WebviewError> {
+    let js_cstr = convert_to_cstring(script.source.as_str())?;
+    let result = sys::webview_user_script_add(
+        webview as *mut _,
+        js_cstr.as_ptr(),
+        script.injection_time.as_raw(),
+        script.main_frame_only as c_int,
+    );
+
+    match result {
+        0 => Ok(()),
+        _ => Err(WebviewError::Unsupported("user scripts")),
+    }
+}
+
+#[inline]
+pub unsafe fn webview_user_style_add(
+    webview: &mut sys::webview,
+    style: &UserStyle,
+) -> Result<(), WebviewError> {
+    let css_cstr = convert_to_cstring(style.source.as_str())?;
+    let result = sys::webview_user_style_add(
         webview as *mut _,
-        Some(callback::dispatch_handler::<T> as DispatchFn),
-        callback,
+        css_cstr.as_ptr(),
+        style.main_frame_only as c_int,
+    );
+
+    match result {
+        0 => Ok(()),
+        _ => Err(WebviewError::Unsupported("user stylesheets")),
+    }
+}
+
+#[inline]
+fn window_result(result: c_int) -> Result<(), WebviewError> {
+    match result {
+        -1 => Err(WebviewError::Unsupported("window geometry")),
+        _ => Ok(()),
+    }
+}
+
+/// Converts a window size into C integers.
+///
+/// Sizes which do not fit into a `c_int` result in a
+/// `WebviewError::InvalidSize`.
+#[inline]
+fn window_size(width: usize, height: usize) -> Result<(c_int, c_int), WebviewError> {
+    match (c_int::try_from(width), c_int::try_from(height)) {
+        (Ok(w), Ok(h)) => Ok((w, h)),
+        _ => Err(WebviewError::InvalidSize(width, height)),
+    }
+}
+
+#[inline]
+pub unsafe fn webview_window_set_size(
+    webview: &mut sys::webview,
+    width: usize,
+    height: usize,
+) -> Result<(), WebviewError> {
+    let (width, height) = window_size(width, height)?;
+    window_result(sys::webview_window_set_size(webview as *mut _, width, height))
+}
+
+#[inline]
+pub unsafe fn webview_window_size(
+    webview: &mut sys::webview,
+) -> Result<(usize, usize), WebviewError> {
+    let (mut width, mut height): (c_int, c_int) = (0, 0);
+    window_result(sys::webview_window_size(
+        webview as *mut _,
+        &mut width,
+        &mut height,
+    ))?;
+    Ok((width as usize, height as usize))
+}
+
+#[inline]
+pub unsafe fn webview_window_set_position(
+    webview: &mut sys::webview,
+    x: i32,
+    y: i32,
+) -> Result<(), WebviewError> {
+    window_result(sys::webview_window_set_position(webview as *mut _, x, y))
+}
+
+#[inline]
+pub unsafe fn webview_window_position(
+    webview: &mut sys::webview,
+) -> Result<(i32, i32), WebviewError> {
+    let (mut x, mut y): (c_int, c_int) = (0, 0);
+    window_result(sys::webview_window_position(
+        webview as *mut _,
+        &mut x,
+        &mut y,
+    ))?;
+    Ok((x, y))
+}
+
+#[inline]
+pub unsafe fn webview_window_set_min_size(
+    webview: &mut sys::webview,
+    width: usize,
+    height: usize,
+) -> Result<(), WebviewError> {
+    let (width, height) = window_size(width, height)?;
+    window_result(sys::webview_window_set_min_size(webview as *mut _, width, height))
+}
+
+#[inline]
+pub unsafe fn webview_window_set_max_size(
+    webview: &mut sys::webview,
+    width: usize,
+    height: usize,
+) -> Result<(), WebviewError> {
+    let (width, height) = window_size(width, height)?;
+    window_result(sys::webview_window_set_max_size(webview as *mut _, width, height))
+}
+
+#[inline]
+pub unsafe fn webview_window_center(webview: &mut sys::webview) -> Result<(), WebviewError> {
+    window_result(sys::webview_window_center(webview as *mut _))
+}
+
+#[inline]
+fn window_option_result(result: c_int) -> Result<(), WebviewError> {
+    match result {
+        -1 => Err(WebviewError::Unsupported("window option")),
+        -2 => Err(WebviewError::InvalidIcon),
+        _ => Ok(()),
+    }
+}
+
+#[inline]
+pub unsafe fn webview_window_set_decorated(
+    webview: &mut sys::webview,
+    decorated: bool,
+) -> Result<(), WebviewError> {
+    window_option_result(sys::webview_window_set_decorated(
+        webview as *mut _,
+        decorated as c_int,
+    ))
+}
+
+#[inline]
+pub unsafe fn webview_window_set_always_on_top(
+    webview: &mut sys::webview,
+    always_on_top: bool,
+) -> Result<(), WebviewError> {
+    let result = sys::webview_window_set_always_on_top(webview as *mut _, always_on_top as c_int);
+    window_option_result(result)
+}
+
+#[inline]
+pub unsafe fn webview_window_set_transparent(
+    webview: &mut sys::webview,
+    transparent: bool,
+) -> Result<(), WebviewError> {
+    let result = sys::webview_window_set_transparent(webview as *mut _, transparent as c_int);
+    window_option_result(result)
+}
+
+#[inline]
+pub unsafe fn webview_window_set_icon(
+    webview: &mut sys::webview,
+    icon: &[u8],
+) -> Result<(), WebviewError> {
+    let result = sys::webview_window_set_icon(
+        webview as *mut _,
+        icon.as_ptr() as *const c_void,
+        icon.len(),
+    );
+    window_option_result(result)
+}
+
+#[inline]
+pub unsafe fn webview_window_set_visible(
+    webview: &mut sys::webview,
+    visible: bool,
+) -> Result<(), WebviewError> {
+    window_option_result(sys::webview_window_set_visible(
+        webview as *mut _,
+        visible as c_int,
+    ))
+}
+
+#[inline]
+pub unsafe fn webview_window_set_maximized(
+    webview: &mut sys::webview,
+    maximized: bool,
+) -> Result<(), WebviewError> {
+    window_option_result(sys::webview_window_set_maximized(
+        webview as *mut _,
+        maximized as c_int,
+    ))
+}
+
+#[inline]
+pub unsafe fn webview_window_set_minimized(
+    webview: &mut sys::webview,
+    minimized: bool,
+) -> Result<(), WebviewError> {
+    window_option_result(sys::webview_window_set_minimized(
+        webview as *mut _,
+        minimized as c_int,
+    ))
+}
+
+#[inline]
+pub unsafe fn webview_window_focus(webview: &mut sys::webview) -> Result<(), WebviewError> {
+    window_option_result(sys::webview_window_focus(webview as *mut _))
+}
+
+#[inline]
+pub unsafe fn webview_window_state(
+    webview: &mut sys::webview,
+) -> Result<WindowState, WebviewError> {
+    match sys::webview_window_state(webview as *mut _) {
+        -1 => Err(WebviewError::Unsupported("window state")),
+        state => Ok(WindowState::from_bits_truncate(state)),
+    }
+}
+
+/// Connects the window state callback for a webview with userdata of type
+/// `T`
+#[inline]
+pub unsafe fn webview_window_state_connect<T>(webview: *mut sys::webview) {
+    sys::webview_window_state_connect(
+        webview,
+        Some(callback::window_state_handler::<T> as WindowStateFn),
     );
 }
 
@@ -276,3 +779,22 @@ pub unsafe fn webview_exit(webview: &mut sys::webview) {
 }
 
 //...debug, print_log
+
+#[cfg(test)]
+mod test {
+    use crate::ffi::*;
+
+    #[test]
+    fn window_sizes() {
+        assert_eq!(window_size(800, 600).unwrap(), (800, 600));
+        assert_eq!(window_size(0, 0).unwrap(), (0, 0));
+
+        let max = c_int::MAX as usize;
+        assert_eq!(window_size(max, 1).unwrap(), (c_int::MAX, 1));
+        match window_size(max + 1, 600) {
+            Err(WebviewError::InvalidSize(width, 600)) => assert_eq!(width, max + 1),
+            _ => panic!("expected invalid size"),
+        }
+        assert!(window_size(800, usize::MAX).is_err());
+    }
+}
@@ -0,0 +1,432 @@
+//! Module for parsing JSON into `JsValue`s.
+//!
+//! This is a small, dependency free parser which is used for decoding
+//! payloads sent from Javascript via `external.invoke`. Strings without
+//! escape sequences are borrowed from the input, so parsing only allocates
+//! for arrays, objects and escaped strings.
+
+use std::borrow::Cow;
+use std::char;
+use std::error::Error;
+use std::fmt;
+
+use crate::value::JsValue;
+
+/// Maximum nesting depth of arrays and objects
+const MAX_DEPTH: usize = 128;
+
+/// Error due to invalid JSON input
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    offset: usize,
+    reason: &'static str,
+}
+
+impl ParseError {
+    /// The byte offset in the input at which the error occurred
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for ParseError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "invalid JSON at offset {}: {}", self.offset, self.reason)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses a complete JSON document.
+#[inline]
+pub fn parse<'s>(json: &'s str) -> Result<JsValue<'s>, ParseError> {
+    let mut parser = Parser { json, pos: 0 };
+    let value = parser.parse_value(0)?;
+    parser.skip_whitespace();
+
+    if parser.pos != json.len() {
+        return Err(parser.error("trailing characters"));
+    }
+
+    Ok(value)
+}
+
+struct Parser<'s> {
+    json: &'s str,
+    pos:  usize,
+}
+
+impl<'s> Parser<'s> {
+    #[inline]
+    fn error(&self, reason: &'static str) -> ParseError {
+        ParseError {
+            offset: self.pos,
+            reason,
+        }
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.json.as_bytes().get(self.pos).cloned()
+    }
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek();
+        self.pos += 1;
+        byte
+    }
+
+    #[inline]
+    fn expect(&mut self, byte: u8, reason: &'static str) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(b) if b == byte => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(self.error(reason)),
+        }
+    }
+
+    #[inline]
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    #[inline]
+    fn parse_literal(
+        &mut self,
+        literal: &str,
+        value: JsValue<'s>,
+    ) -> Result<JsValue<'s>, ParseError> {
+        if self.json[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(value)
+        } else {
+            Err(self.error("invalid literal"))
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<JsValue<'s>, ParseError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("maximum nesting depth exceeded"));
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.parse_literal("null", JsValue::Null),
+            Some(b't') => self.parse_literal("true", JsValue::Bool(true)),
+            Some(b'f') => self.parse_literal("false", JsValue::Bool(false)),
+            Some(b'"') => self.parse_string().map(JsValue::Str),
+            Some(b'[') => self.parse_array(depth),
+            Some(b'{') => self.parse_object(depth),
+            Some(b'-') | Some(b'0'..=b'9') => self.parse_number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> Result<JsValue<'s>, ParseError> {
+        self.pos += 1;
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(JsValue::Array(values));
+        }
+
+        loop {
+            values.push(self.parse_value(depth + 1)?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(b',') => continue,
+                Some(b']') => return Ok(JsValue::Array(values)),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("expected `,` or `]`"));
+                }
+            }
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Result<JsValue<'s>, ParseError> {
+        self.pos += 1;
+        let mut entries = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(JsValue::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected object key"));
+            }
+
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(b':', "expected `:`")?;
+            entries.push((key, self.parse_value(depth + 1)?));
+
+            self.skip_whitespace();
+            match self.next() {
+                Some(b',') => continue,
+                Some(b'}') => return Ok(JsValue::Object(entries)),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("expected `,` or `}`"));
+                }
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JsValue<'s>, ParseError> {
+        let start = self.pos;
+        let mut integer = true;
+
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+
+        match self.next() {
+            Some(b'0') => {}
+            Some(b'1'..=b'9') => self.skip_digits(),
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("expected digit"));
+            }
+        }
+
+        if self.peek() == Some(b'.') {
+            integer = false;
+            self.pos += 1;
+            self.expect_digits()?;
+        }
+
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            integer = false;
+            self.pos += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.pos += 1;
+            }
+            self.expect_digits()?;
+        }
+
+        let number = &self.json[start..self.pos];
+        if integer {
+            if let Ok(val) = number.parse::<u64>() {
+                return Ok(JsValue::UInt(val));
+            }
+            if let Ok(val) = number.parse::<i64>() {
+                return Ok(JsValue::Int(val));
+            }
+        }
+
+        number
+            .parse::<f64>()
+            .map(JsValue::Float)
+            .map_err(|_| ParseError {
+                offset: start,
+                reason: "invalid number",
+            })
+    }
+
+    #[inline]
+    fn skip_digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    #[inline]
+    fn expect_digits(&mut self) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(b'0'..=b'9') => {
+                self.skip_digits();
+                Ok(())
+            }
+            _ => Err(self.error("expected digit")),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<Cow<'s, str>, ParseError> {
+        self.pos += 1;
+        let start = self.pos;
+
+        // fast path: strings without any escape sequences are borrowed
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(Cow::Borrowed(&self.json[start..self.pos - 1]));
+                }
+                Some(b'\\') => break,
+                Some(0x00..=0x1f) => return Err(self.error("control character in string")),
+                Some(_) => self.pos += 1,
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+
+        let mut string = String::from(&self.json[start..self.pos]);
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(Cow::Owned(string));
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let ch = self.parse_escape()?;
+                    string.push(ch);
+                }
+                Some(0x00..=0x1f) => return Err(self.error("control character in string")),
+                Some(_) => {
+                    // the input is valid UTF-8, so copy until the next special byte
+                    let rest = &self.json[self.pos..];
+                    let len = rest
+                        .bytes()
+                        .position(|b| b == b'"' || b == b'\\' || b < 0x20)
+                        .unwrap_or(rest.len());
+                    string.push_str(&rest[..len]);
+                    self.pos += len;
+                }
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<char, ParseError> {
+        let ch = match self.next() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                let high = self.parse_hex4()?;
+                match high {
+                    0xD800..=0xDBFF => {
+                        if !self.json[self.pos..].starts_with("\\u") {
+                            return Err(self.error("unpaired surrogate"));
+                        }
+                        self.pos += 2;
+                        let low = self.parse_hex4()?;
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return Err(self.error("unpaired surrogate"));
+                        }
+
+                        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                        char::from_u32(code).ok_or_else(|| self.error("invalid code point"))?
+                    }
+                    0xDC00..=0xDFFF => return Err(self.error("unpaired surrogate")),
+                    code => char::from_u32(code).ok_or_else(|| self.error("invalid code point"))?,
+                }
+            }
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("invalid escape sequence"));
+            }
+        };
+
+        Ok(ch)
+    }
+
+    #[inline]
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        let hex = self
+            .json
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        let code =
+            u32::from_str_radix(hex, 16).map_err(|_| self.error("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+
+    use crate::json::*;
+
+    #[test]
+    fn primitives() {
+        assert_eq!(parse("null"), Ok(JsValue::Null));
+        assert_eq!(parse(" true "), Ok(JsValue::Bool(true)));
+        assert_eq!(parse("false"), Ok(JsValue::Bool(false)));
+        assert_eq!(parse("42"), Ok(JsValue::UInt(42)));
+        assert_eq!(parse("-42"), Ok(JsValue::Int(-42)));
+        assert_eq!(parse("-0.5e1"), Ok(JsValue::Float(-5.0)));
+        assert_eq!(
+            parse("18446744073709551616"),
+            Ok(JsValue::Float(18446744073709551616.0))
+        );
+    }
+
+    #[test]
+    fn strings() {
+        match parse(r#""borrowed""#).unwrap() {
+            JsValue::Str(Cow::Borrowed(string)) => assert_eq!(string, "borrowed"),
+            _ => panic!("unnecessary allocation"),
+        };
+
+        assert_eq!(
+            parse(r#""a\"b\\c\n\u00fc\ud83e\udd80 ü""#),
+            Ok(JsValue::from("a\"b\\c\nü🦀 ü"))
+        );
+    }
+
+    #[test]
+    fn nested() {
+        assert_eq!(
+            parse(r#"{"a": [1, -2, {"b": null}], "c": {}}"#),
+            Ok(JsValue::object(vec![
+                (
+                    "a",
+                    JsValue::array(vec![
+                        JsValue::UInt(1),
+                        JsValue::Int(-2),
+                        JsValue::object(vec![("b", ())])
+                    ])
+                ),
+                ("c", JsValue::Object(vec![])),
+            ]))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        for invalid in &[
+            "",
+            "nul",
+            "01",
+            "1.",
+            "-",
+            "[1,]",
+            "[1 2]",
+            "{\"a\" 1}",
+            "{1: 2}",
+            "\"abc",
+            "\"\\x\"",
+            "\"\\ud800\"",
+            "\"\n\"",
+            "1 2",
+        ] {
+            assert!(parse(invalid).is_err(), "{:?} accepted", invalid);
+        }
+
+        assert_eq!(parse("[1, x]").unwrap_err().offset(), 4);
+        assert!(parse(&"[".repeat(MAX_DEPTH + 2)).is_err());
+    }
+}
@@ -20,30 +20,76 @@
 
 #[macro_use]
 extern crate bitflags;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 extern crate webview_sys;
 
 use std::borrow::Cow;
 use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 
+pub use crate::assets::{generate_assets, Asset, Assets};
 pub use crate::builder::Builder;
+pub use crate::callback::ExternalInvoke;
 pub use crate::content::Content;
+pub use crate::dialog::{AlertLevel, FileDialog, FileDialogKind};
+pub use crate::dispatch::DispatchHandle;
+pub use crate::document::HtmlDocument;
+#[cfg(feature = "serde")]
+pub use crate::eval::JsonArg;
 pub use crate::eval::{Arg, EvalBuffer, StringBuffers};
 pub use crate::ffi::{Dialog, Flags};
+pub use crate::json::ParseError;
+pub use crate::navigation::{NavigationRequest, NavigationType, Policy};
+pub use crate::router::{InvokeError, Responder, Router};
+pub use crate::scheme::SchemeResponse;
+#[cfg(feature = "server")]
+pub use crate::server::AssetServer;
+pub use crate::timer::TimerHandle;
+pub use crate::user_content::{InjectionTime, UserScript, UserStyle};
+pub use crate::value::JsValue;
+pub use crate::window::{InitialState, WindowState};
 
+use crate::dispatch::DispatchQueue;
 use crate::error::WebviewError;
+use crate::lifecycle::Hooks;
+use crate::navigation::NavigationFnBox;
+use crate::scheme::SchemeFnBox;
+use crate::timer::Timers;
 use webview_sys as sys;
 
+mod assets;
 mod builder;
 mod callback;
 mod content;
 mod conversion;
+mod dialog;
+mod dispatch;
+mod document;
 mod error;
 mod eval;
+mod event;
 mod ffi;
+mod json;
+mod lifecycle;
+mod navigation;
+mod router;
+mod scheme;
+#[cfg(feature = "server")]
+mod server;
+mod timer;
+mod user_content;
+mod value;
+mod window;
 
 /// Type alias for a boxed internal invoke callback.
-type ExternalInvokeFnBox<'invoke, T> = Box<FnMut(&mut Webview, &mut T, &str) + 'invoke>;
+type ExternalInvokeFnBox<'invoke, T> = Box<dyn ExternalInvoke<T> + 'invoke>;
 type Result = std::result::Result<(), WebviewError>;
 
 /// Outer wrapper
@@ -59,17 +105,29 @@ pub struct WebviewWrapper<'invoke, T> {
 
 /// Inner wrapper
 ///
-/// A wrapper struct for the actual C library struct and the associated string
-/// buffers
+/// A wrapper struct for the actual C library struct, the associated string
+/// buffers, the queue for dispatched closures and the registered timers
 #[repr(C)]
 pub struct Webview {
-    webview: sys::webview,
-    buffers: StringBuffers,
+    webview:       sys::webview,
+    buffers:       StringBuffers,
+    queue:         Arc<DispatchQueue>,
+    timers:        Timers,
+    assets:        Option<Assets>,
+    #[cfg(feature = "server")]
+    server:        Option<AssetServer>,
+    https_default: bool,
+    /// Records evaluated Javascript instead of passing it to the C library
+    #[cfg(test)]
+    eval_sink:     Option<Vec<String>>,
 }
 
 struct Extension<'invoke, T> {
-    external_invoke: Option<ExternalInvokeFnBox<'invoke, T>>,
-    userdata:        T,
+    external_invoke:    Option<ExternalInvokeFnBox<'invoke, T>>,
+    navigation_handler: Option<NavigationFnBox<'invoke>>,
+    schemes:            HashMap<String, SchemeFnBox<'invoke>>,
+    hooks:              Hooks<'invoke, T>,
+    userdata:           T,
 }
 
 impl Webview {
@@ -88,11 +146,10 @@ impl Webview {
         self.buffers.buffer.clear();
         self.buffers.buffer.push_str(js);
 
-        unsafe { ffi::webview_eval(&mut self.webview, self.buffers.buffer.nul_terminated())? };
-        Ok(())
+        self.eval_buffer()
     }
 
-    /// Evaluate a single Javascript function with simple arguments
+    /// Evaluate a single Javascript function with arbitrarily nested arguments
     #[inline]
     pub fn eval_fn<'s>(&mut self, function: &str, args: &[Arg<'s>]) -> Result {
         self.buffers.buffer.clear();
@@ -108,8 +165,58 @@ impl Webview {
         }
         self.buffers.buffer.push_str(");");
 
-        unsafe { ffi::webview_eval(&mut self.webview, self.buffers.buffer.nul_terminated())? };
-        Ok(())
+        self.eval_buffer()
+    }
+
+    /// Evaluate a single Javascript function with arguments of any type
+    /// implementing `Serialize`
+    ///
+    /// The arguments are serialized as JSON directly into the evaluation
+    /// buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `WebviewError::Json` if any argument fails to serialize.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub fn eval_fn_serde(&mut self, function: &str, args: &[&dyn JsonArg]) -> Result {
+        self.buffers.buffer.clear();
+        self.buffers.buffer.push_str(function);
+        self.buffers.buffer.push('(');
+
+        let mut iter = args.iter().peekable();
+        while let Some(arg) = iter.next() {
+            arg.push_json(&mut self.buffers.buffer)?;
+            if iter.peek().is_some() {
+                self.buffers.buffer.push(',');
+            }
+        }
+        self.buffers.buffer.push_str(");");
+
+        self.eval_buffer()
+    }
+
+    /// Evaluate the Javascript in the evaluation buffer
+    #[inline]
+    fn eval_buffer(&mut self) -> Result {
+        #[cfg(test)]
+        {
+            if let Some(ref mut sink) = self.eval_sink {
+                sink.push(self.buffers.buffer.as_str().to_owned());
+                return Ok(());
+            }
+        }
+
+        unsafe { ffi::webview_eval(&mut self.webview, self.buffers.buffer.nul_terminated()) }
+    }
+
+    /// Emit an event to all Javascript handlers subscribed with
+    /// `window.rust.on(event, handler)`
+    ///
+    /// The payload is passed as the only argument to each handler.
+    #[inline]
+    pub fn emit<'s>(&mut self, event: &str, payload: impl Into<JsValue<'s>>) -> Result {
+        event::emit(self, event, payload.into())
     }
 
     /// Inject CSS in string format at runtime
@@ -126,6 +233,106 @@ impl Webview {
         Ok(())
     }
 
+    /// Navigate to new content (a URL, a file or HTML markup)
+    ///
+    /// The navigation is completed asynchronously, so Javascript evaluated
+    /// immediately afterwards may still apply to the previous page.
+    /// Use the `on_load_finished` hook of the `Builder` to wait for the new
+    /// page.
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::Unsupported` is returned if runtime navigation is not
+    /// supported on the current platform, a `WebviewError::InvalidPath` if a
+    /// file to navigate to does not exist and a `WebviewError::InvalidUrl` if
+    /// a URL is malformed.
+    #[inline]
+    pub fn navigate<'content, C>(&mut self, content: impl Into<Content<'content, C>>) -> Result
+    where
+        C: Into<Cow<'content, str>>,
+    {
+        let url: Cow<'content, str> = match content.into() {
+            Content::Assets(assets) => {
+                self.set_assets(assets)?;
+                Cow::Borrowed(assets::ASSETS_URL)
+            }
+            #[cfg(feature = "server")]
+            Content::Served(server) => {
+                let url = server.url();
+                self.server = Some(server);
+                Cow::Owned(url)
+            }
+            Content::File(path) => {
+                let path = path.into();
+                Cow::Owned(content::file_url(Path::new(path.as_ref()))?)
+            }
+            Content::Url(url) => content::normalize_url(url.into(), self.https_default)?,
+            content => content.into_url(),
+        };
+
+        unsafe { ffi::webview_navigate(&mut self.webview, url) }
+    }
+
+    /// Add a script, which is injected into every page loaded afterwards
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::Unsupported` is returned if user scripts are not
+    /// supported on the current platform.
+    #[inline]
+    pub fn add_user_script(&mut self, script: UserScript) -> Result {
+        unsafe { ffi::webview_user_script_add(&mut self.webview, &script) }
+    }
+
+    /// Add a stylesheet, which is injected into every page loaded afterwards
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::Unsupported` is returned if user stylesheets are not
+    /// supported on the current platform.
+    #[inline]
+    pub fn add_user_style(&mut self, style: UserStyle) -> Result {
+        unsafe { ffi::webview_user_style_add(&mut self.webview, &style) }
+    }
+
+    /// Navigate to a bundle of assets, which replaces any previously served
+    /// assets
+    #[inline]
+    pub fn navigate_assets(&mut self, assets: Assets) -> Result {
+        self.navigate(Content::<&str>::Assets(assets))
+    }
+
+    /// Load HTML markup directly, relative URLs in the markup are resolved
+    /// against the optional base URL
+    #[inline]
+    pub fn load_html<'html, 'base>(
+        &mut self,
+        html: impl Into<Cow<'html, str>>,
+        base_url: Option<&'base str>,
+    ) -> Result {
+        unsafe { ffi::webview_load_html(&mut self.webview, html, base_url) }
+    }
+
+    /// Reload the current page
+    #[inline]
+    pub fn reload(&mut self) -> Result {
+        unsafe { ffi::webview_reload(&mut self.webview) }
+    }
+
+    /// Navigate to the previous page in the history, returns `false` if there
+    /// is no previous page
+    #[inline]
+    pub fn go_back(&mut self) -> std::result::Result<bool, WebviewError> {
+        unsafe { ffi::webview_go_back(&mut self.webview) }
+    }
+
+    /// Navigate to the next page in the history, returns `false` if there is
+    /// no next page
+    #[inline]
+    pub fn go_forward(&mut self) -> std::result::Result<bool, WebviewError> {
+        unsafe { ffi::webview_go_forward(&mut self.webview) }
+    }
+
     /// Set the webview window title
     #[inline]
     pub fn set_title<'title>(&mut self, title: impl Into<Cow<'title, str>>) -> Result {
@@ -147,6 +354,156 @@ impl Webview {
         };
     }
 
+    /// Resize the webview window
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::Unsupported` is returned by all window geometry
+    /// methods if the window geometry can not be controlled on the current
+    /// platform. A `WebviewError::InvalidSize` is returned by all sizing
+    /// methods for sizes which exceed the platform's range and by this method
+    /// for a width or height of zero.
+    #[inline]
+    pub fn set_size(&mut self, width: usize, height: usize) -> Result {
+        if width == 0 || height == 0 {
+            return Err(WebviewError::InvalidSize(width, height));
+        }
+
+        unsafe { ffi::webview_window_set_size(&mut self.webview, width, height) }
+    }
+
+    /// Get the current size of the webview window
+    #[inline]
+    pub fn size(&mut self) -> std::result::Result<(usize, usize), WebviewError> {
+        unsafe { ffi::webview_window_size(&mut self.webview) }
+    }
+
+    /// Move the webview window to a position on the screen
+    ///
+    /// Window managers may ignore or adjust the requested position.
+    #[inline]
+    pub fn set_position(&mut self, x: i32, y: i32) -> Result {
+        unsafe { ffi::webview_window_set_position(&mut self.webview, x, y) }
+    }
+
+    /// Get the current position of the webview window on the screen
+    #[inline]
+    pub fn position(&mut self) -> std::result::Result<(i32, i32), WebviewError> {
+        unsafe { ffi::webview_window_position(&mut self.webview) }
+    }
+
+    /// Set the minimum size of the webview window, a size of zero removes the
+    /// constraint
+    #[inline]
+    pub fn set_min_size(&mut self, width: usize, height: usize) -> Result {
+        unsafe { ffi::webview_window_set_min_size(&mut self.webview, width, height) }
+    }
+
+    /// Set the maximum size of the webview window, a size of zero removes the
+    /// constraint
+    #[inline]
+    pub fn set_max_size(&mut self, width: usize, height: usize) -> Result {
+        unsafe { ffi::webview_window_set_max_size(&mut self.webview, width, height) }
+    }
+
+    /// Center the webview window on its current monitor
+    #[inline]
+    pub fn center(&mut self) -> Result {
+        unsafe { ffi::webview_window_center(&mut self.webview) }
+    }
+
+    /// Show or hide the window decorations, i.e. the title bar and borders
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::Unsupported` is returned by all window option methods
+    /// if the option is not supported on the current platform.
+    #[inline]
+    pub fn set_decorations(&mut self, decorations: bool) -> Result {
+        unsafe { ffi::webview_window_set_decorated(&mut self.webview, decorations) }
+    }
+
+    /// Keep the webview window above all other windows
+    #[inline]
+    pub fn set_always_on_top(&mut self, always_on_top: bool) -> Result {
+        unsafe { ffi::webview_window_set_always_on_top(&mut self.webview, always_on_top) }
+    }
+
+    /// Make the background of the webview window transparent
+    ///
+    /// This requires a compositing window manager. Since the window has to be
+    /// recreated internally, it may briefly disappear.
+    #[inline]
+    pub fn set_transparent(&mut self, transparent: bool) -> Result {
+        unsafe { ffi::webview_window_set_transparent(&mut self.webview, transparent) }
+    }
+
+    /// Set the window icon from encoded image data (e.g. PNG)
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::InvalidIcon` is returned if the image data can not be
+    /// decoded.
+    #[inline]
+    pub fn set_icon(&mut self, icon: &[u8]) -> Result {
+        unsafe { ffi::webview_window_set_icon(&mut self.webview, icon) }
+    }
+
+    /// Show the webview window, e.g. if it has been built as hidden
+    #[inline]
+    pub fn show(&mut self) -> Result {
+        unsafe { ffi::webview_window_set_visible(&mut self.webview, true) }
+    }
+
+    /// Hide the webview window, the webview keeps running in the background
+    #[inline]
+    pub fn hide(&mut self) -> Result {
+        unsafe { ffi::webview_window_set_visible(&mut self.webview, false) }
+    }
+
+    /// Minimize the webview window
+    #[inline]
+    pub fn minimize(&mut self) -> Result {
+        unsafe { ffi::webview_window_set_minimized(&mut self.webview, true) }
+    }
+
+    /// Maximize the webview window
+    #[inline]
+    pub fn maximize(&mut self) -> Result {
+        unsafe { ffi::webview_window_set_maximized(&mut self.webview, true) }
+    }
+
+    /// Restore the webview window from its maximized size
+    #[inline]
+    pub fn unmaximize(&mut self) -> Result {
+        unsafe { ffi::webview_window_set_maximized(&mut self.webview, false) }
+    }
+
+    /// Bring the webview window to the front and give it the input focus
+    ///
+    /// A minimized window is restored. Window managers may decline to move
+    /// the focus and only mark the window as requiring attention instead.
+    #[inline]
+    pub fn focus(&mut self) -> Result {
+        unsafe { ffi::webview_window_focus(&mut self.webview) }
+    }
+
+    /// Get the current state of the webview window
+    #[inline]
+    pub fn window_state(&mut self) -> std::result::Result<WindowState, WebviewError> {
+        unsafe { ffi::webview_window_state(&mut self.webview) }
+    }
+
+    #[inline]
+    pub fn is_visible(&mut self) -> std::result::Result<bool, WebviewError> {
+        Ok(self.window_state()?.contains(WindowState::Visible))
+    }
+
+    #[inline]
+    pub fn is_focused(&mut self) -> std::result::Result<bool, WebviewError> {
+        Ok(self.window_state()?.contains(WindowState::Focused))
+    }
+
     #[inline]
     pub fn dialog<'title, 'arg>(
         &mut self,
@@ -159,21 +516,205 @@ impl Webview {
         unsafe { ffi::webview_dialog(&mut self.webview, dialog, flags, title, arg, result_buffer) }
     }
 
+    /// Show a dialog for selecting a file to open, returns `None` if the
+    /// dialog is cancelled
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::TruncatedPath` is returned by all file dialogs if the
+    /// selected path exceeds the maximum path length.
+    #[inline]
+    pub fn open_file(&mut self) -> std::result::Result<Option<PathBuf>, WebviewError> {
+        self.open_dialog("Open File", Flags::File)
+    }
+
+    /// Show a dialog for selecting a directory, returns `None` if the dialog
+    /// is cancelled
+    #[inline]
+    pub fn open_directory(&mut self) -> std::result::Result<Option<PathBuf>, WebviewError> {
+        self.open_dialog("Open Directory", Flags::Directory)
+    }
+
+    /// Show a dialog for selecting the path to save a file at, returns `None`
+    /// if the dialog is cancelled
+    ///
+    /// The default name is suggested as file name. If an existing file is
+    /// selected, the user is asked to confirm overwriting it.
+    #[inline]
+    pub fn save_file<'name>(
+        &mut self,
+        default_name: impl Into<Cow<'name, str>>,
+    ) -> std::result::Result<Option<PathBuf>, WebviewError> {
+        let mut buffer = dialog::path_buffer();
+        unsafe {
+            ffi::webview_dialog_save(&mut self.webview, "Save File", default_name, &mut buffer)?
+        };
+        dialog::parse_path(&buffer)
+    }
+
+    /// Show a configurable file dialog and wait until it is closed, returns
+    /// the selected paths (none if the dialog is cancelled)
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::Unsupported` is returned if configurable file dialogs
+    /// are not supported on the current platform.
     #[inline]
-    pub fn dispatch<T>(&mut self, func: impl FnMut(&mut Webview, &mut T)) {
-        unsafe { ffi::webview_dispatch(&mut self.webview, &func) };
+    pub fn show_file_dialog(
+        &mut self,
+        dialog: &FileDialog,
+    ) -> std::result::Result<Vec<PathBuf>, WebviewError> {
+        unsafe { ffi::webview_file_dialog(&mut self.webview, dialog) }
+    }
+
+    /// Show an alert dialog with a message and wait until it is closed
+    #[inline]
+    pub fn alert<'title, 'msg>(
+        &mut self,
+        level: AlertLevel,
+        title: impl Into<Cow<'title, str>>,
+        message: impl Into<Cow<'msg, str>>,
+    ) -> Result {
+        let (dialog, flags) = (Dialog::Alert, level.as_flags());
+        unsafe { ffi::webview_dialog(&mut self.webview, dialog, flags, title, message, &mut []) }
+    }
+
+    #[inline]
+    fn open_dialog(
+        &mut self,
+        title: &str,
+        flags: Flags,
+    ) -> std::result::Result<Option<PathBuf>, WebviewError> {
+        let (dialog, mut buffer) = (Dialog::Open, dialog::path_buffer());
+        unsafe { ffi::webview_dialog(&mut self.webview, dialog, flags, title, "", &mut buffer)? };
+        dialog::parse_path(&buffer)
+    }
+
+    /// Dispatch a function call
+    ///
+    /// The specified function is queued and executed on the main thread during
+    /// a later iteration of the main loop.
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::DispatchFailed` is returned if the webview is about to
+    /// be exited.
+    #[inline]
+    pub fn dispatch(&mut self, func: impl FnOnce(&mut Webview) + Send + 'static) -> Result {
+        self.queue.push(dispatch::webview_job(func))
+    }
+
+    /// Execute a function once on the main thread after the given delay
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::Unsupported` is returned if timers are not supported
+    /// on the current platform.
+    #[inline]
+    pub fn set_timeout(
+        &mut self,
+        delay: Duration,
+        func: impl FnOnce(&mut Webview) + 'static,
+    ) -> std::result::Result<TimerHandle, WebviewError> {
+        unsafe {
+            self.timers.add(
+                &mut self.webview,
+                delay,
+                false,
+                timer::webview_timeout(func),
+            )
+        }
+    }
+
+    /// Execute a function repeatedly on the main thread after each interval
+    /// until the timer is cancelled
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::Unsupported` is returned if timers are not supported
+    /// on the current platform.
+    #[inline]
+    pub fn set_interval(
+        &mut self,
+        interval: Duration,
+        func: impl FnMut(&mut Webview) + 'static,
+    ) -> std::result::Result<TimerHandle, WebviewError> {
+        unsafe {
+            self.timers.add(
+                &mut self.webview,
+                interval,
+                true,
+                timer::webview_interval(func),
+            )
+        }
+    }
+
+    /// Cancel a timer, returns `false` if the timer has already been executed
+    /// or cancelled
+    #[inline]
+    pub fn clear_timer(&mut self, handle: TimerHandle) -> bool {
+        self.timers.remove(handle)
     }
 
     #[inline]
     pub fn terminate(&mut self) {
         unsafe { ffi::webview_terminate(&mut self.webview) };
     }
+
+    /// Replace the served assets, the internal scheme is registered when
+    /// assets are set for the first time.
+    #[inline]
+    fn set_assets(&mut self, assets: Assets) -> Result {
+        if self.assets.is_none() {
+            unsafe { ffi::webview_assets_register(&mut self.webview)? };
+        }
+
+        self.assets = Some(assets);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl Webview {
+    /// Creates a webview, which is never initialized by the C library, for
+    /// testing functions that only evaluate Javascript, which is recorded
+    /// instead of being evaluated.
+    ///
+    /// The webview must not be dropped, since it can not be exited.
+    pub(crate) fn uninitialized() -> std::mem::ManuallyDrop<Self> {
+        use std::ffi::CString;
+
+        std::mem::ManuallyDrop::new(Self {
+            webview: unsafe { std::mem::zeroed() },
+            buffers: StringBuffers::new(CString::default(), CString::default(), 64),
+            queue: Arc::new(DispatchQueue::new()),
+            timers: Timers::new(),
+            assets: None,
+            #[cfg(feature = "server")]
+            server: None,
+            https_default: false,
+            eval_sink: Some(Vec::new()),
+        })
+    }
+
+    /// Executes all dispatched jobs and returns the evaluated Javascript.
+    pub(crate) fn drain_evaluated(&mut self) -> Vec<String> {
+        let queue = Arc::clone(&self.queue);
+        unsafe { queue.drain(&mut self.webview) };
+        self.eval_sink.as_mut().map_or_else(Vec::new, std::mem::take)
+    }
 }
 
 impl Drop for Webview {
     #[inline]
     fn drop(&mut self) {
-        unsafe { ffi::webview_exit(&mut self.webview) };
+        self.queue.close();
+        self.timers.clear();
+        unsafe {
+            // schemes are registered for all webviews of the process
+            ffi::webview_scheme_unregister_all(&mut self.webview);
+            ffi::webview_exit(&mut self.webview);
+        }
     }
 }
 
@@ -195,7 +736,8 @@ impl<'invoke, T> WebviewHandle<'invoke, T> {
     /// Start the webview event loop
     ///
     /// The loop iterates until either the webview window is closed or a
-    /// call to `terminate` is made.
+    /// call to `terminate` is made. The `on_exit` hook is called once the loop
+    /// has been exited.
     #[inline]
     pub fn run(&mut self, blocking: bool) {
         use ffi::LoopResult::Exit;
@@ -205,6 +747,14 @@ impl<'invoke, T> WebviewHandle<'invoke, T> {
                 break;
             }
         }
+
+        // jobs dispatched after termination would never be executed
+        self.webview().queue.close();
+
+        if let Some(mut func) = self.extension_mut().hooks.on_exit.take() {
+            let wrapper = unsafe { &mut *self.inner.get() };
+            func(&mut wrapper.inner, &mut wrapper.ext.userdata);
+        }
     }
 
     #[inline]
@@ -217,11 +767,69 @@ impl<'invoke, T> WebviewHandle<'invoke, T> {
         self.webview_mut().eval_fn(function, args)
     }
 
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub fn eval_fn_serde(&mut self, function: &str, args: &[&dyn JsonArg]) -> Result {
+        self.webview_mut().eval_fn_serde(function, args)
+    }
+
+    #[inline]
+    pub fn emit<'s>(&mut self, event: &str, payload: impl Into<JsValue<'s>>) -> Result {
+        self.webview_mut().emit(event, payload)
+    }
+
     #[inline]
     pub fn inject_css(&mut self, css: &str) -> Result {
         self.webview_mut().inject_css(css)
     }
 
+    #[inline]
+    pub fn navigate<'content, C>(&mut self, content: impl Into<Content<'content, C>>) -> Result
+    where
+        C: Into<Cow<'content, str>>,
+    {
+        self.webview_mut().navigate(content)
+    }
+
+    #[inline]
+    pub fn navigate_assets(&mut self, assets: Assets) -> Result {
+        self.webview_mut().navigate_assets(assets)
+    }
+
+    #[inline]
+    pub fn add_user_script(&mut self, script: UserScript) -> Result {
+        self.webview_mut().add_user_script(script)
+    }
+
+    #[inline]
+    pub fn add_user_style(&mut self, style: UserStyle) -> Result {
+        self.webview_mut().add_user_style(style)
+    }
+
+    #[inline]
+    pub fn load_html<'html, 'base>(
+        &mut self,
+        html: impl Into<Cow<'html, str>>,
+        base_url: Option<&'base str>,
+    ) -> Result {
+        self.webview_mut().load_html(html, base_url)
+    }
+
+    #[inline]
+    pub fn reload(&mut self) -> Result {
+        self.webview_mut().reload()
+    }
+
+    #[inline]
+    pub fn go_back(&mut self) -> std::result::Result<bool, WebviewError> {
+        self.webview_mut().go_back()
+    }
+
+    #[inline]
+    pub fn go_forward(&mut self) -> std::result::Result<bool, WebviewError> {
+        self.webview_mut().go_forward()
+    }
+
     #[inline]
     pub fn set_title<'title>(&mut self, title: impl Into<Cow<'title, str>>) -> Result {
         self.webview_mut().set_title(title)
@@ -237,6 +845,106 @@ impl<'invoke, T> WebviewHandle<'invoke, T> {
         self.webview_mut().set_color(color);
     }
 
+    #[inline]
+    pub fn set_size(&mut self, width: usize, height: usize) -> Result {
+        self.webview_mut().set_size(width, height)
+    }
+
+    #[inline]
+    pub fn size(&mut self) -> std::result::Result<(usize, usize), WebviewError> {
+        self.webview_mut().size()
+    }
+
+    #[inline]
+    pub fn set_position(&mut self, x: i32, y: i32) -> Result {
+        self.webview_mut().set_position(x, y)
+    }
+
+    #[inline]
+    pub fn position(&mut self) -> std::result::Result<(i32, i32), WebviewError> {
+        self.webview_mut().position()
+    }
+
+    #[inline]
+    pub fn set_min_size(&mut self, width: usize, height: usize) -> Result {
+        self.webview_mut().set_min_size(width, height)
+    }
+
+    #[inline]
+    pub fn set_max_size(&mut self, width: usize, height: usize) -> Result {
+        self.webview_mut().set_max_size(width, height)
+    }
+
+    #[inline]
+    pub fn center(&mut self) -> Result {
+        self.webview_mut().center()
+    }
+
+    #[inline]
+    pub fn set_decorations(&mut self, decorations: bool) -> Result {
+        self.webview_mut().set_decorations(decorations)
+    }
+
+    #[inline]
+    pub fn set_always_on_top(&mut self, always_on_top: bool) -> Result {
+        self.webview_mut().set_always_on_top(always_on_top)
+    }
+
+    #[inline]
+    pub fn set_transparent(&mut self, transparent: bool) -> Result {
+        self.webview_mut().set_transparent(transparent)
+    }
+
+    #[inline]
+    pub fn set_icon(&mut self, icon: &[u8]) -> Result {
+        self.webview_mut().set_icon(icon)
+    }
+
+    #[inline]
+    pub fn show(&mut self) -> Result {
+        self.webview_mut().show()
+    }
+
+    #[inline]
+    pub fn hide(&mut self) -> Result {
+        self.webview_mut().hide()
+    }
+
+    #[inline]
+    pub fn minimize(&mut self) -> Result {
+        self.webview_mut().minimize()
+    }
+
+    #[inline]
+    pub fn maximize(&mut self) -> Result {
+        self.webview_mut().maximize()
+    }
+
+    #[inline]
+    pub fn unmaximize(&mut self) -> Result {
+        self.webview_mut().unmaximize()
+    }
+
+    #[inline]
+    pub fn focus(&mut self) -> Result {
+        self.webview_mut().focus()
+    }
+
+    #[inline]
+    pub fn window_state(&mut self) -> std::result::Result<WindowState, WebviewError> {
+        self.webview_mut().window_state()
+    }
+
+    #[inline]
+    pub fn is_visible(&mut self) -> std::result::Result<bool, WebviewError> {
+        self.webview_mut().is_visible()
+    }
+
+    #[inline]
+    pub fn is_focused(&mut self) -> std::result::Result<bool, WebviewError> {
+        self.webview_mut().is_focused()
+    }
+
     #[inline]
     pub fn dialog<'title, 'arg>(
         &mut self,
@@ -251,8 +959,101 @@ impl<'invoke, T> WebviewHandle<'invoke, T> {
     }
 
     #[inline]
-    pub fn dispatch(&mut self, func: impl FnMut(&mut Webview, &mut T)) {
-        self.webview_mut().dispatch(func);
+    pub fn open_file(&mut self) -> std::result::Result<Option<PathBuf>, WebviewError> {
+        self.webview_mut().open_file()
+    }
+
+    #[inline]
+    pub fn open_directory(&mut self) -> std::result::Result<Option<PathBuf>, WebviewError> {
+        self.webview_mut().open_directory()
+    }
+
+    #[inline]
+    pub fn save_file<'name>(
+        &mut self,
+        default_name: impl Into<Cow<'name, str>>,
+    ) -> std::result::Result<Option<PathBuf>, WebviewError> {
+        self.webview_mut().save_file(default_name)
+    }
+
+    #[inline]
+    pub fn show_file_dialog(
+        &mut self,
+        dialog: &FileDialog,
+    ) -> std::result::Result<Vec<PathBuf>, WebviewError> {
+        self.webview_mut().show_file_dialog(dialog)
+    }
+
+    #[inline]
+    pub fn alert<'title, 'msg>(
+        &mut self,
+        level: AlertLevel,
+        title: impl Into<Cow<'title, str>>,
+        message: impl Into<Cow<'msg, str>>,
+    ) -> Result {
+        self.webview_mut().alert(level, title, message)
+    }
+
+    #[inline]
+    pub fn dispatch(&mut self, func: impl FnOnce(&mut Webview, &mut T) + Send + 'static) -> Result
+    where
+        T: 'static,
+    {
+        self.webview()
+            .queue
+            .push(unsafe { dispatch::wrapper_job(func) })
+    }
+
+    /// Execute a function once on the main thread after the given delay
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::Unsupported` is returned if timers are not supported
+    /// on the current platform.
+    #[inline]
+    pub fn set_timeout(
+        &mut self,
+        delay: Duration,
+        func: impl FnOnce(&mut Webview, &mut T) + 'static,
+    ) -> std::result::Result<TimerHandle, WebviewError>
+    where
+        T: 'static,
+    {
+        let webview = self.webview_mut();
+        unsafe {
+            let func = timer::wrapper_timeout(func);
+            webview.timers.add(&mut webview.webview, delay, false, func)
+        }
+    }
+
+    /// Execute a function repeatedly on the main thread after each interval
+    /// until the timer is cancelled
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::Unsupported` is returned if timers are not supported
+    /// on the current platform.
+    #[inline]
+    pub fn set_interval(
+        &mut self,
+        interval: Duration,
+        func: impl FnMut(&mut Webview, &mut T) + 'static,
+    ) -> std::result::Result<TimerHandle, WebviewError>
+    where
+        T: 'static,
+    {
+        let webview = self.webview_mut();
+        unsafe {
+            let func = timer::wrapper_interval(func);
+            webview
+                .timers
+                .add(&mut webview.webview, interval, true, func)
+        }
+    }
+
+    #[inline]
+    pub fn clear_timer(&mut self, handle: TimerHandle) -> bool {
+        self.webview_mut().clear_timer(handle)
     }
 
     #[inline]
@@ -273,7 +1074,8 @@ impl<'invoke, T> WebviewHandle<'invoke, T> {
     #[inline]
     pub fn thread_handle(&self) -> ThreadHandle<'invoke, T> {
         ThreadHandle {
-            inner: Arc::downgrade(&self.inner),
+            queue:  Arc::downgrade(&self.webview().queue),
+            marker: PhantomData,
         }
     }
 
@@ -303,12 +1105,12 @@ unsafe impl<'invoke, T> Sync for ThreadHandle<'invoke, T> where T: Sync {}
 
 /// A handle that is safe to share between threads
 ///
-/// The thread handle contains a weak reference to the webview contained within
-/// the main handle, so it can only be used to dispatch function calls while
-/// the main handle exists.
-#[derive(Clone)]
+/// The thread handle contains a weak reference to the dispatch queue of the
+/// webview contained within the main handle, so it can only be used to
+/// dispatch function calls while the main handle exists.
 pub struct ThreadHandle<'invoke, T> {
-    inner: Weak<UnsafeCell<WebviewWrapper<'invoke, T>>>,
+    queue:  Weak<DispatchQueue>,
+    marker: PhantomData<*const WebviewWrapper<'invoke, T>>,
 }
 
 impl<'invoke, T> ThreadHandle<'invoke, T> {
@@ -316,6 +1118,8 @@ impl<'invoke, T> ThreadHandle<'invoke, T> {
     ///
     /// The specified function is queued for execution on the main thread in a
     /// thread safe manner and asynchronously executed.
+    /// Since the function is owned by the queue until it is executed, it may
+    /// move any captured values in.
     ///
     /// # Errors
     ///
@@ -323,12 +1127,76 @@ impl<'invoke, T> ThreadHandle<'invoke, T> {
     /// `WebviewError::DispatchFailed` is returned, if the dispatch is attempted
     /// when the main handle no longer exists.
     #[inline]
-    pub fn try_dispatch(&self, func: impl FnMut(&mut Webview, &mut T) + Send) -> Result {
-        self.inner
-            .upgrade()
-            .map(|cell| {
-                let webview = unsafe { &mut (*cell.get()).inner };
-                webview.dispatch(func);
-            }).ok_or(WebviewError::DispatchFailed)
+    pub fn try_dispatch(
+        &self,
+        func: impl FnOnce(&mut Webview, &mut T) + Send + 'static,
+    ) -> Result
+    where
+        T: 'static,
+    {
+        let queue = self.queue.upgrade().ok_or(WebviewError::DispatchFailed)?;
+        queue.push(unsafe { dispatch::wrapper_job(func) })
+    }
+
+    /// Attempt to emit an event to all subscribed Javascript handlers
+    ///
+    /// The event is emitted on the main thread in a later iteration of the
+    /// main loop.
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::DispatchFailed` is returned if the main handle no
+    /// longer exists.
+    #[inline]
+    pub fn emit(
+        &self,
+        event: impl Into<String>,
+        payload: impl Into<JsValue<'static>>,
+    ) -> Result {
+        let queue = self.queue.upgrade().ok_or(WebviewError::DispatchFailed)?;
+        let (event, payload) = (event.into(), payload.into());
+        queue.push(dispatch::webview_job(move |webview| {
+            // evaluation errors can not be reported back to the caller
+            let _ = webview.emit(&event, payload);
+        }))
+    }
+
+    /// Attempt to dispatch a function call and retrieve its return value
+    ///
+    /// The function is queued just like with `try_dispatch`, the returned
+    /// `DispatchHandle` can be used to either block until the return value is
+    /// available or to await it asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::DispatchFailed` is returned if the main handle no
+    /// longer exists.
+    /// The `DispatchHandle` resolves to the same error if the webview is
+    /// terminated or dropped before the function could be executed.
+    #[inline]
+    pub fn dispatch_with_result<R>(
+        &self,
+        func: impl FnOnce(&mut Webview, &mut T) -> R + Send + 'static,
+    ) -> std::result::Result<DispatchHandle<R>, WebviewError>
+    where
+        T: 'static,
+        R: Send + 'static,
+    {
+        let (completer, handle) = dispatch::completion();
+        self.try_dispatch(move |webview, userdata| {
+            completer.complete(func(webview, userdata));
+        })?;
+
+        Ok(handle)
+    }
+}
+
+impl<'invoke, T> Clone for ThreadHandle<'invoke, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            queue:  Weak::clone(&self.queue),
+            marker: PhantomData,
+        }
     }
 }
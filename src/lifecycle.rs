@@ -0,0 +1,46 @@
+//! Module for hooks into the lifecycle of a webview.
+//!
+//! Except for `on_exit`, the hooks are called by the C library through
+//! `callback::lifecycle_handler` (or `callback::window_state_handler` for
+//! window state changes) and are currently only supported by the GTK backend.
+
+use crate::window::WindowState;
+use crate::Webview;
+
+/// Type alias for a boxed lifecycle hook.
+pub type HookFnBox<'invoke, T> = Box<dyn FnMut(&mut Webview, &mut T) + 'invoke>;
+/// Type alias for a boxed hook deciding whether the window may be closed.
+pub type CloseFnBox<'invoke, T> = Box<dyn FnMut(&mut Webview, &mut T) -> bool + 'invoke>;
+/// Type alias for a boxed hook receiving the new state of the window.
+pub type WindowStateFnBox<'invoke, T> = Box<dyn FnMut(&mut Webview, &mut T, WindowState) + 'invoke>;
+
+/// The lifecycle hooks of a webview
+pub struct Hooks<'invoke, T> {
+    pub on_ready:           Option<HookFnBox<'invoke, T>>,
+    pub on_load_finished:   Option<HookFnBox<'invoke, T>>,
+    pub on_close_requested: Option<CloseFnBox<'invoke, T>>,
+    pub on_exit:            Option<HookFnBox<'invoke, T>>,
+    pub on_window_state:    Option<WindowStateFnBox<'invoke, T>>,
+}
+
+impl<'invoke, T> Hooks<'invoke, T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            on_ready:           None,
+            on_load_finished:   None,
+            on_close_requested: None,
+            on_exit:            None,
+            on_window_state:    None,
+        }
+    }
+
+    /// Returns `true` if any hooks requiring callbacks from the C library are
+    /// set.
+    #[inline]
+    pub fn requires_connect(&self) -> bool {
+        self.on_ready.is_some()
+            || self.on_load_finished.is_some()
+            || self.on_close_requested.is_some()
+    }
+}
@@ -0,0 +1,97 @@
+//! Module for deciding whether navigations are allowed.
+//!
+//! The navigation handler set with `Builder::set_navigation_handler` is called
+//! through `callback::navigation_handler` for every navigation of the top
+//! frame, including the initial load of the content, and for requests to open
+//! new windows. This is currently only supported by the GTK backend.
+
+use webview_sys as sys;
+
+/// Type alias for a boxed navigation handler.
+pub type NavigationFnBox<'invoke> = Box<dyn FnMut(&NavigationRequest) -> Policy + 'invoke>;
+
+/// Decision of the navigation handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Load the URL in the webview (also for new window requests)
+    Allow,
+    /// Ignore the navigation
+    Deny,
+    /// Open the URL with the default application (e.g. the system browser)
+    /// instead
+    OpenExternally,
+}
+
+impl Policy {
+    #[inline]
+    pub(crate) fn as_raw(self) -> i32 {
+        match self {
+            Policy::Allow => sys::WEBVIEW_POLICY_ALLOW,
+            Policy::Deny => sys::WEBVIEW_POLICY_DENY,
+            Policy::OpenExternally => sys::WEBVIEW_POLICY_OPEN_EXTERNALLY,
+        }
+    }
+}
+
+/// The cause of a navigation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationType {
+    LinkClicked,
+    FormSubmitted,
+    BackForward,
+    Reload,
+    FormResubmitted,
+    Other,
+}
+
+impl From<i32> for NavigationType {
+    /// Conversion from the values of `WebKitNavigationType`
+    #[inline]
+    fn from(raw: i32) -> Self {
+        match raw {
+            0 => NavigationType::LinkClicked,
+            1 => NavigationType::FormSubmitted,
+            2 => NavigationType::BackForward,
+            3 => NavigationType::Reload,
+            4 => NavigationType::FormResubmitted,
+            _ => NavigationType::Other,
+        }
+    }
+}
+
+/// A navigation to be decided upon by the navigation handler
+#[derive(Debug, Clone, Copy)]
+pub struct NavigationRequest<'a> {
+    url:             &'a str,
+    navigation_type: NavigationType,
+    new_window:      bool,
+}
+
+impl<'a> NavigationRequest<'a> {
+    #[inline]
+    pub(crate) fn new(url: &'a str, navigation_type: NavigationType, new_window: bool) -> Self {
+        Self {
+            url,
+            navigation_type,
+            new_window,
+        }
+    }
+
+    /// The target URL of the navigation
+    #[inline]
+    pub fn url(&self) -> &'a str {
+        self.url
+    }
+
+    #[inline]
+    pub fn navigation_type(&self) -> NavigationType {
+        self.navigation_type
+    }
+
+    /// Returns `true` if the page requested to open the URL in a new window
+    /// (e.g. by a link with `target="_blank"` or `window.open`).
+    #[inline]
+    pub fn is_new_window(&self) -> bool {
+        self.new_window
+    }
+}
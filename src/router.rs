@@ -0,0 +1,550 @@
+//! Module for routing Javascript invocations to named command handlers.
+//!
+//! A `Router` can be passed to `Builder::set_external_invoke` in place of a
+//! closure. It injects a small Javascript shim, which encodes each call of
+//! `window.rust.call('name', args)` as the command name followed by a colon
+//! and the JSON encoded arguments (e.g. `name:{"id":1}`). Plain invocations
+//! without a colon (e.g. `external.invoke('name')`) are routed with `null`
+//! arguments.
+//!
+//! Calls of `window.rust.invoke('name', args)` return a promise and
+//! additionally encode a call ID between command name and arguments (e.g.
+//! `name:7:{"id":1}`), which is unambiguous, since no valid JSON starts with a
+//! number followed by a colon. The promise is settled by evaluating
+//! `window.rust.__settle` with the same call ID through a `Responder`.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Weak};
+
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+
+use crate::callback::ExternalInvoke;
+use crate::dispatch::{self, DispatchQueue};
+use crate::error::WebviewError;
+use crate::eval::EvalBuffer;
+use crate::value::JsValue;
+use crate::Webview;
+
+type RouteFnBox<'invoke, T> = Box<
+    dyn FnMut(&mut Webview, &mut T, &str, &mut Option<Responder>) -> Result<(), InvokeError>
+        + 'invoke,
+>;
+type ErrorFnBox<'invoke, T> = Box<dyn FnMut(&mut Webview, &mut T, InvokeError) + 'invoke>;
+
+/// Separator between the command name and the JSON encoded arguments
+const SEPARATOR: char = ':';
+
+/// Errors due to invalid invocations from Javascript
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvokeError {
+    /// No handler is registered for the command
+    UnknownCommand(String),
+    /// The arguments for the command could not be parsed
+    MalformedPayload { command: String, reason: String },
+}
+
+impl fmt::Display for InvokeError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            InvokeError::UnknownCommand(ref command) => write!(f, "unknown command `{}`", command),
+            InvokeError::MalformedPayload {
+                ref command,
+                ref reason,
+            } => write!(
+                f,
+                "malformed arguments for command `{}`: {}",
+                command, reason
+            ),
+        }
+    }
+}
+
+impl Error for InvokeError {}
+
+/// Dispatcher for routing invocations from Javascript to handlers registered
+/// by command name
+///
+/// Errors due to unknown commands or malformed arguments are passed to the
+/// error handler set with `on_error` and are otherwise ignored.
+pub struct Router<'invoke, T> {
+    routes:        HashMap<String, RouteFnBox<'invoke, T>>,
+    error_handler: Option<ErrorFnBox<'invoke, T>>,
+}
+
+impl<'invoke, T> Router<'invoke, T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            routes:        HashMap::new(),
+            error_handler: None,
+        }
+    }
+
+    /// Registers a handler receiving the parsed arguments of the command.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the command name contains a colon.
+    #[inline]
+    pub fn route(
+        self,
+        command: impl Into<String>,
+        mut handler: impl FnMut(&mut Webview, &mut T, JsValue) + 'invoke,
+    ) -> Self {
+        let command = command.into();
+        let name = command.clone();
+        self.insert(
+            command,
+            Box::new(move |webview, userdata, payload, _| {
+                let args = parse_args(&name, payload)?;
+                handler(webview, userdata, args);
+                Ok(())
+            }),
+        )
+    }
+
+    /// Registers a handler receiving the arguments of the command
+    /// deserialized into any type implementing `Deserialize`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the command name contains a colon.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub fn route_typed<A>(
+        self,
+        command: impl Into<String>,
+        mut handler: impl FnMut(&mut Webview, &mut T, A) + 'invoke,
+    ) -> Self
+    where
+        A: DeserializeOwned,
+    {
+        let command = command.into();
+        let name = command.clone();
+        self.insert(
+            command,
+            Box::new(move |webview, userdata, payload, _| {
+                let args =
+                    serde_json::from_str(payload).map_err(|err| InvokeError::MalformedPayload {
+                        command: name.clone(),
+                        reason:  err.to_string(),
+                    })?;
+
+                handler(webview, userdata, args);
+                Ok(())
+            }),
+        )
+    }
+
+    /// Registers a handler whose return value settles the promise returned by
+    /// `window.rust.invoke`.
+    ///
+    /// An `Ok` value resolves the promise, an `Err` value rejects it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the command name contains a colon.
+    #[inline]
+    pub fn route_promise<R, E>(
+        self,
+        command: impl Into<String>,
+        mut handler: impl FnMut(&mut Webview, &mut T, JsValue) -> Result<R, E> + 'invoke,
+    ) -> Self
+    where
+        R: Into<JsValue<'static>>,
+        E: Into<JsValue<'static>>,
+    {
+        self.route_deferred(command, move |webview, userdata, args, responder| {
+            // the promise may already be rejected if the webview is exiting
+            let _ = match handler(webview, userdata, args) {
+                Ok(value) => responder.resolve(value),
+                Err(reason) => responder.reject(reason),
+            };
+        })
+    }
+
+    /// Registers a handler which receives a `Responder` for settling the
+    /// promise returned by `window.rust.invoke`.
+    ///
+    /// The `Responder` can be moved to another thread for responding to long
+    /// running requests. If it is dropped without responding, the promise is
+    /// rejected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the command name contains a colon.
+    #[inline]
+    pub fn route_deferred(
+        self,
+        command: impl Into<String>,
+        mut handler: impl FnMut(&mut Webview, &mut T, JsValue, Responder) + 'invoke,
+    ) -> Self {
+        let command = command.into();
+        let name = command.clone();
+        self.insert(
+            command,
+            Box::new(move |webview, userdata, payload, responder| {
+                let args = parse_args(&name, payload)?;
+                let responder = responder.take().expect("responder has already been taken");
+                handler(webview, userdata, args, responder);
+                Ok(())
+            }),
+        )
+    }
+
+    /// Sets the handler for unknown commands and malformed arguments.
+    #[inline]
+    pub fn on_error(
+        mut self,
+        handler: impl FnMut(&mut Webview, &mut T, InvokeError) + 'invoke,
+    ) -> Self {
+        self.error_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Generates the Javascript shim defining `window.rust.call`,
+    /// `window.rust.invoke` and `window.rust.commands`, the list of all
+    /// registered command names.
+    #[inline]
+    pub fn js_shim(&self) -> String {
+        let mut buffer = EvalBuffer::new(256);
+        buffer.push_str("(function(){var rust=window.rust=window.rust||{};rust.commands=[");
+
+        let mut commands: Vec<&String> = self.routes.keys().collect();
+        commands.sort();
+        for (idx, command) in commands.into_iter().enumerate() {
+            if idx > 0 {
+                buffer.push(',');
+            }
+            buffer.push_js_string(command);
+        }
+
+        buffer.push_str(
+            "];\
+             function encode(args){return JSON.stringify(args===undefined?null:args);}\
+             rust.call=function(name,args){window.external.invoke(name+':'+encode(args));};\
+             var pending={},nextId=1;\
+             rust.invoke=function(name,args){return new Promise(function(resolve,reject){\
+             var id=nextId++;pending[id]={resolve:resolve,reject:reject};\
+             window.external.invoke(name+':'+id+':'+encode(args));});};\
+             rust.__settle=function(id,ok,value){var p=pending[id];if(p){delete pending[id];\
+             (ok?p.resolve:p.reject)(value);}};})();",
+        );
+        buffer.as_str().to_owned()
+    }
+
+    #[inline]
+    fn insert(mut self, command: String, route: RouteFnBox<'invoke, T>) -> Self {
+        assert!(
+            !command.contains(SEPARATOR),
+            "command names must not contain `{}`",
+            SEPARATOR
+        );
+
+        self.routes.insert(command, route);
+        self
+    }
+}
+
+impl<'invoke, T> Default for Router<'invoke, T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'invoke, T> ExternalInvoke<T> for Router<'invoke, T> {
+    #[inline]
+    fn invoke(&mut self, webview: &mut Webview, userdata: &mut T, arg: &str) {
+        let (command, id, payload) = split_payload(arg);
+        let mut responder = Some(Responder::new(id, &webview.queue));
+        let result = match self.routes.get_mut(command) {
+            Some(route) => route(webview, userdata, payload, &mut responder),
+            None => Err(InvokeError::UnknownCommand(command.to_owned())),
+        };
+
+        // routes without a response resolve with `null`, errors are rejected
+        // with the error message, both are ignored if the webview is exiting
+        let _ = match (responder, &result) {
+            (Some(responder), Ok(_)) => responder.resolve(JsValue::Null),
+            (Some(responder), Err(err)) => responder.reject(err.to_string()),
+            (None, _) => Ok(()),
+        };
+
+        if let Err(err) = result {
+            if let Some(handler) = self.error_handler.as_mut() {
+                handler(webview, userdata, err);
+            }
+        }
+    }
+
+    #[inline]
+    fn init_script(&self) -> Option<String> {
+        Some(self.js_shim())
+    }
+}
+
+/// Handle for settling the promise returned by a call of
+/// `window.rust.invoke`
+///
+/// The `Responder` can be sent to other threads and settles the promise by
+/// dispatching a function call to the main thread. Responders for calls of
+/// `window.rust.call` silently discard any response.
+pub struct Responder {
+    id:      Option<u64>,
+    queue:   Weak<DispatchQueue>,
+    settled: bool,
+}
+
+impl Responder {
+    #[inline]
+    fn new(id: Option<u64>, queue: &Arc<DispatchQueue>) -> Self {
+        Self {
+            id,
+            queue: Arc::downgrade(queue),
+            settled: false,
+        }
+    }
+
+    /// Resolves the promise with the given value.
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::DispatchFailed` is returned if the webview no longer
+    /// exists or has been terminated.
+    #[inline]
+    pub fn resolve(mut self, value: impl Into<JsValue<'static>>) -> Result<(), WebviewError> {
+        self.settle(true, value.into())
+    }
+
+    /// Rejects the promise with the given reason.
+    ///
+    /// # Errors
+    ///
+    /// A `WebviewError::DispatchFailed` is returned if the webview no longer
+    /// exists or has been terminated.
+    #[inline]
+    pub fn reject(mut self, reason: impl Into<JsValue<'static>>) -> Result<(), WebviewError> {
+        self.settle(false, reason.into())
+    }
+
+    #[inline]
+    fn settle(&mut self, ok: bool, value: JsValue<'static>) -> Result<(), WebviewError> {
+        self.settled = true;
+        let id = match self.id {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+
+        let queue = self.queue.upgrade().ok_or(WebviewError::DispatchFailed)?;
+        queue.push(dispatch::webview_job(move |webview| {
+            // evaluation errors can not be reported back to the caller
+            let _ = webview.eval_fn(
+                "window.rust.__settle",
+                &[JsValue::UInt(id), JsValue::Bool(ok), value],
+            );
+        }))
+    }
+}
+
+impl Drop for Responder {
+    #[inline]
+    fn drop(&mut self) {
+        if !self.settled {
+            let _ = self.settle(false, JsValue::from("request dropped without response"));
+        }
+    }
+}
+
+#[inline]
+fn parse_args<'s>(command: &str, payload: &'s str) -> Result<JsValue<'s>, InvokeError> {
+    JsValue::from_json(payload).map_err(|err| InvokeError::MalformedPayload {
+        command: command.to_owned(),
+        reason:  err.to_string(),
+    })
+}
+
+/// Splits an invocation into command name, optional call ID and JSON encoded
+/// arguments.
+#[inline]
+fn split_payload(arg: &str) -> (&str, Option<u64>, &str) {
+    let (command, rest) = match arg.find(SEPARATOR) {
+        Some(idx) => (&arg[..idx], &arg[idx + 1..]),
+        None => return (arg, None, "null"),
+    };
+
+    if let Some(idx) = rest.find(SEPARATOR) {
+        if let Ok(id) = rest[..idx].parse() {
+            return (command, Some(id), &rest[idx + 1..]);
+        }
+    }
+
+    (command, None, rest)
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::router::*;
+
+    #[test]
+    fn split() {
+        assert_eq!(split_payload("reset"), ("reset", None, "null"));
+        assert_eq!(
+            split_payload("set:{\"a\":\"b:c\"}"),
+            ("set", None, "{\"a\":\"b:c\"}")
+        );
+        assert_eq!(split_payload(":1"), ("", None, "1"));
+        assert_eq!(split_payload("get:7:[1]"), ("get", Some(7), "[1]"));
+        assert_eq!(split_payload("get:7:\"a:b\""), ("get", Some(7), "\"a:b\""));
+        assert_eq!(split_payload("get:\"7:b\""), ("get", None, "\"7:b\""));
+    }
+
+    #[test]
+    fn shim() {
+        let router = Router::<()>::new()
+            .route("reset", |_, _, _| {})
+            .route("it's", |_, _, _| {});
+
+        assert!(router
+            .js_shim()
+            .contains("rust.commands=['it\\'s','reset'];"));
+    }
+
+    #[test]
+    fn promise() {
+        let mut router = Router::<u32>::new().route_promise("div", |_, calls, args| {
+            *calls += 1;
+            match args
+                .as_array()
+                .map(|args| (args[0].as_i64(), args[1].as_i64()))
+            {
+                Some((Some(_), Some(0))) => Err("division by zero"),
+                Some((Some(a), Some(b))) => Ok(a / b),
+                _ => Err("invalid arguments"),
+            }
+        });
+
+        let mut webview = Webview::uninitialized();
+        let mut calls = 0;
+
+        router.invoke(&mut webview, &mut calls, "div:1:[6,3]");
+        assert_eq!(
+            webview.drain_evaluated(),
+            ["window.rust.__settle(1,true,2);"]
+        );
+
+        router.invoke(&mut webview, &mut calls, "div:2:[6,0]");
+        assert_eq!(
+            webview.drain_evaluated(),
+            ["window.rust.__settle(2,false,'division by zero');"]
+        );
+
+        // calls of `window.rust.call` are never settled
+        router.invoke(&mut webview, &mut calls, "div:[6,3]");
+        assert!(webview.drain_evaluated().is_empty());
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn route_resolved_with_null() {
+        let mut router = Router::<u32>::new().route("reset", |_, calls, args| {
+            assert!(args.is_null());
+            *calls += 1;
+        });
+
+        let mut webview = Webview::uninitialized();
+        let mut calls = 0;
+
+        router.invoke(&mut webview, &mut calls, "reset:4:null");
+        assert_eq!(
+            webview.drain_evaluated(),
+            ["window.rust.__settle(4,true,null);"]
+        );
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn invalid_invocations() {
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        let handler_errors = Rc::clone(&errors);
+        let mut router = Router::<()>::new()
+            .route_promise("get", |_, _, _| Ok::<_, JsValue>(JsValue::Null))
+            .on_error(move |_, _, err| handler_errors.borrow_mut().push(err));
+
+        let mut webview = Webview::uninitialized();
+
+        router.invoke(&mut webview, &mut (), "unknown:1:null");
+        assert_eq!(
+            webview.drain_evaluated(),
+            ["window.rust.__settle(1,false,'unknown command `unknown`');"]
+        );
+
+        router.invoke(&mut webview, &mut (), "get:2:{");
+        let evaluated = webview.drain_evaluated();
+        assert_eq!(evaluated.len(), 1);
+        assert!(evaluated[0]
+            .starts_with("window.rust.__settle(2,false,'malformed arguments for command `get`: "));
+
+        let errors = errors.borrow();
+        assert_eq!(errors[0], InvokeError::UnknownCommand("unknown".to_owned()));
+        match errors[1] {
+            InvokeError::MalformedPayload { ref command, .. } => assert_eq!(command, "get"),
+            ref err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn deferred() {
+        let responders = Rc::new(RefCell::new(Vec::new()));
+        let route_responders = Rc::clone(&responders);
+        let mut router = Router::<()>::new().route_deferred("load", move |_, _, _, responder| {
+            route_responders.borrow_mut().push(responder);
+        });
+
+        let mut webview = Webview::uninitialized();
+
+        // the promise is not settled until the responder is used or dropped
+        router.invoke(&mut webview, &mut (), "load:1:null");
+        router.invoke(&mut webview, &mut (), "load:2:null");
+        assert!(webview.drain_evaluated().is_empty());
+
+        let (first, second) = {
+            let mut responders = responders.borrow_mut();
+            let second = responders.pop().unwrap();
+            (responders.pop().unwrap(), second)
+        };
+
+        first.resolve(vec!["a", "b"]).unwrap();
+        drop(second);
+        assert_eq!(
+            webview.drain_evaluated(),
+            [
+                "window.rust.__settle(1,true,['a','b']);",
+                "window.rust.__settle(2,false,'request dropped without response');",
+            ]
+        );
+    }
+
+    #[test]
+    fn responder_after_close() {
+        let mut webview = Webview::uninitialized();
+        let responder = Responder::new(Some(1), &webview.queue);
+
+        webview.queue.close();
+        assert!(responder.resolve(JsValue::Null).is_err());
+        assert!(webview.drain_evaluated().is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_command() {
+        Router::<()>::new().route("a:b", |_, _, _| {});
+    }
+}
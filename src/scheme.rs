@@ -0,0 +1,138 @@
+//! Module for serving content through custom URI schemes.
+//!
+//! Requests for a scheme registered with `Builder::register_scheme` (e.g.
+//! `app://localhost/index.html`) are answered from Rust through
+//! `callback::scheme_handler`, so an entire frontend can be served from memory
+//! and relative URLs are resolved as usual.
+//! This is currently only supported by the GTK backend.
+
+use std::borrow::Cow;
+use std::os::raw::c_void;
+
+use crate::assets::{Assets, ASSETS_SCHEME};
+use crate::content;
+use crate::ffi;
+
+/// Type alias for a boxed custom scheme handler.
+///
+/// The handler receives the path of the requested URL.
+pub type SchemeFnBox<'invoke> = Box<dyn FnMut(&str) -> Option<SchemeResponse> + 'invoke>;
+
+/// Schemes which can not be registered, since they are handled by the
+/// webview itself
+const RESERVED_SCHEMES: &[&str] = &[
+    "about",
+    "blob",
+    "data",
+    "file",
+    "http",
+    "https",
+    "javascript",
+];
+
+/// Response to a custom scheme request
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemeResponse {
+    data:      Cow<'static, [u8]>,
+    mime_type: Cow<'static, str>,
+}
+
+impl SchemeResponse {
+    /// Creates a response, static data (e.g. from `include_bytes!`) is not
+    /// copied.
+    #[inline]
+    pub fn new(
+        data: impl Into<Cow<'static, [u8]>>,
+        mime_type: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            data:      data.into(),
+            mime_type: mime_type.into(),
+        }
+    }
+
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    #[inline]
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+}
+
+/// Returns `true` if the scheme name is valid and not reserved.
+///
+/// Scheme names must start with a lowercase ASCII letter followed by
+/// lowercase ASCII letters, digits, `+`, `-` or `.`.
+#[inline]
+pub fn is_valid_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    let valid = match chars.next() {
+        Some('a'..='z') => chars.all(|ch| matches!(ch, 'a'..='z' | '0'..='9' | '+' | '-' | '.')),
+        _ => false,
+    };
+
+    valid && scheme != ASSETS_SCHEME && !RESERVED_SCHEMES.contains(&scheme)
+}
+
+/// Answers a request for the `Assets` of a webview.
+///
+/// The requested path is still percent-encoded, whereas assets are stored by
+/// their decoded paths.
+#[inline]
+pub unsafe fn respond_asset(request: *mut c_void, assets: Option<&Assets>, path: &str) {
+    let asset = match (assets, content::percent_decode(path)) {
+        (Some(assets), Some(path)) => assets.resolve(&path),
+        _ => None,
+    };
+
+    match asset {
+        Some(asset) => {
+            if ffi::webview_scheme_finish(request, asset.data(), asset.mime_type()).is_err() {
+                ffi::webview_scheme_fail(request, "invalid MIME type");
+            }
+        }
+        None => ffi::webview_scheme_fail(request, "not found"),
+    }
+}
+
+/// Answers a custom scheme request, an absent response results in a
+/// "not found" error.
+#[inline]
+pub unsafe fn respond(request: *mut c_void, response: Option<SchemeResponse>) {
+    match response {
+        Some(response) => {
+            if ffi::webview_scheme_finish(request, &response.data, &response.mime_type).is_err() {
+                ffi::webview_scheme_fail(request, "invalid MIME type");
+            }
+        }
+        None => ffi::webview_scheme_fail(request, "not found"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scheme::*;
+
+    #[test]
+    fn valid_schemes() {
+        for scheme in &["app", "my-app", "app+v1.2"] {
+            assert!(is_valid_scheme(scheme), "{:?} rejected", scheme);
+        }
+
+        for scheme in &[
+            "",
+            "App",
+            "1app",
+            "app:",
+            "my app",
+            "http",
+            "file",
+            ASSETS_SCHEME,
+        ] {
+            assert!(!is_valid_scheme(scheme), "{:?} accepted", scheme);
+        }
+    }
+}
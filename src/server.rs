@@ -0,0 +1,553 @@
+//! Module for serving content through a loopback HTTP server.
+//!
+//! The `AssetServer` is bound to `127.0.0.1` on an ephemeral port and serves
+//! either a directory or an in-memory `Assets` bundle. Each server generates a
+//! random access token, which the webview passes as query parameter with its
+//! first request. The server then sets a cookie and redirects, so all further
+//! requests (including `fetch` and service workers) are authorized by the
+//! cookie. Requests without a valid token or cookie are rejected, so other
+//! local processes can not access the served content.
+//!
+//! The server runs in a background thread until it is dropped, which happens
+//! along with the `WebviewHandle` if it has been passed as `Content::Served`.
+
+use std::collections::hash_map::RandomState;
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::assets::{self, Assets};
+use crate::content::percent_decode;
+
+/// Name of the cookie and query parameter carrying the access token
+const TOKEN_NAME: &str = "webview_rs_token";
+/// Maximum size of the request head (request line and headers)
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+/// Timeout for reading the entire request head from a connection
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum number of connections which are handled concurrently
+const MAX_CONNECTIONS: usize = 32;
+
+/// The content served by an `AssetServer`
+enum Source {
+    Dir(PathBuf),
+    Assets(Assets),
+}
+
+/// A minimal HTTP server for local content bound to the loopback interface
+pub struct AssetServer {
+    addr:     SocketAddr,
+    token:    Arc<str>,
+    shutdown: Arc<AtomicBool>,
+    thread:   Option<JoinHandle<()>>,
+}
+
+impl AssetServer {
+    /// Starts a server for the contents of a directory.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the directory does not exist or the server can not be bound.
+    #[inline]
+    pub fn serve_dir(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().canonicalize()?;
+        if !dir.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not a directory",
+            ));
+        }
+
+        Self::start(Source::Dir(dir))
+    }
+
+    /// Starts a server for a bundle of assets.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the server can not be bound.
+    #[inline]
+    pub fn serve_assets(assets: Assets) -> io::Result<Self> {
+        Self::start(Source::Assets(assets))
+    }
+
+    /// The local address the server is bound to
+    #[inline]
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The URL of the root index including the access token
+    #[inline]
+    pub fn url(&self) -> String {
+        format!("http://{}/?{}={}", self.addr, TOKEN_NAME, self.token)
+    }
+
+    /// Stops the server and waits for the background thread to finish.
+    ///
+    /// No further connections are accepted afterwards. Connections which have
+    /// already been accepted are handled by detached threads, which are not
+    /// waited for and may still complete their current request.
+    #[inline]
+    pub fn shutdown(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.shutdown.store(true, Ordering::SeqCst);
+            // wake up the blocking `accept`
+            let _ = TcpStream::connect(self.addr);
+            let _ = thread.join();
+        }
+    }
+
+    fn start(source: Source) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::new(127, 0, 0, 1), 0))?;
+        let addr = listener.local_addr()?;
+        let token: Arc<str> = Arc::from(random_token());
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let (token, shutdown) = (Arc::clone(&token), Arc::clone(&shutdown));
+            let source = Arc::new(source);
+            let connections = Arc::new(AtomicUsize::new(0));
+            thread::Builder::new()
+                .name(String::from("webview-asset-server"))
+                .spawn(move || {
+                    for stream in listener.incoming() {
+                        if shutdown.load(Ordering::SeqCst) {
+                            break;
+                        }
+
+                        // connections beyond the limit are closed right away,
+                        // before a thread is spawned for them
+                        let guard = match ConnectionGuard::acquire(&connections) {
+                            Some(guard) => guard,
+                            None => continue,
+                        };
+
+                        if let Ok(stream) = stream {
+                            let (source, token) = (Arc::clone(&source), Arc::clone(&token));
+                            let _ = thread::Builder::new().spawn(move || {
+                                let _guard = guard;
+                                let _ = handle_connection(stream, &source, &token);
+                            });
+                        }
+                    }
+                })?
+        };
+
+        Ok(Self {
+            addr,
+            token,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for AssetServer {
+    #[inline]
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// A slot of the limited number of concurrently handled connections, which is
+/// released when the guard is dropped
+struct ConnectionGuard(Arc<AtomicUsize>);
+
+impl ConnectionGuard {
+    fn acquire(connections: &Arc<AtomicUsize>) -> Option<Self> {
+        if connections.fetch_add(1, Ordering::SeqCst) < MAX_CONNECTIONS {
+            Some(ConnectionGuard(Arc::clone(connections)))
+        } else {
+            connections.fetch_sub(1, Ordering::SeqCst);
+            None
+        }
+    }
+}
+
+impl Drop for ConnectionGuard {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A connection, which fails to read once the deadline for the request head
+/// has passed, so slowly sending clients are cut off as well
+struct DeadlineReader {
+    stream:   TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let now = Instant::now();
+        if now >= self.deadline {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "request timed out"));
+        }
+
+        self.stream.set_read_timeout(Some(self.deadline - now))?;
+        self.stream.read(buf)
+    }
+}
+
+/// A parsed HTTP request head
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    path:   String,
+    query:  Option<String>,
+    cookie: Option<String>,
+}
+
+/// A response status with its reason phrase
+type Status = (u16, &'static str);
+
+const OK: Status = (200, "OK");
+const FOUND: Status = (302, "Found");
+const BAD_REQUEST: Status = (400, "Bad Request");
+const FORBIDDEN: Status = (403, "Forbidden");
+const NOT_FOUND: Status = (404, "Not Found");
+const METHOD_NOT_ALLOWED: Status = (405, "Method Not Allowed");
+
+fn handle_connection(stream: TcpStream, source: &Source, token: &str) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let reader = DeadlineReader {
+        stream,
+        deadline: Instant::now() + READ_TIMEOUT,
+    };
+
+    // the limit applies before any line is buffered, so lines without a line
+    // break can not grow without bound
+    let mut reader = BufReader::new(reader.take(MAX_HEAD_SIZE as u64));
+    let request = match read_request(&mut reader)? {
+        Some(request) => request,
+        None => return write_error(&mut writer, BAD_REQUEST),
+    };
+
+    let head = match request.method.as_str() {
+        "GET" => false,
+        "HEAD" => true,
+        _ => return write_error(&mut writer, METHOD_NOT_ALLOWED),
+    };
+
+    let query_token = request
+        .query
+        .as_ref()
+        .and_then(|query| find_pair(query, '&', TOKEN_NAME));
+    let cookie_token = request
+        .cookie
+        .as_ref()
+        .and_then(|cookie| find_pair(cookie, ';', TOKEN_NAME));
+
+    // the token in the query sets the cookie and redirects to the bare path
+    if query_token == Some(token) {
+        let cookie = format!(
+            "{}={}; Path=/; HttpOnly; SameSite=Strict",
+            TOKEN_NAME, token
+        );
+        let headers = [
+            ("Location", request.path.as_str()),
+            ("Set-Cookie", cookie.as_str()),
+        ];
+        write_response(&mut writer, FOUND, &headers, &[], false)?;
+        return writer.shutdown(Shutdown::Both);
+    }
+
+    if cookie_token != Some(token) {
+        return write_error(&mut writer, FORBIDDEN);
+    }
+
+    let path = match percent_decode(&request.path) {
+        Some(path) => path,
+        None => return write_error(&mut writer, BAD_REQUEST),
+    };
+
+    match *source {
+        Source::Assets(ref assets) => match assets.resolve(&path) {
+            Some(asset) => {
+                let headers = [("Content-Type", asset.mime_type())];
+                write_response(&mut writer, OK, &headers, asset.data(), head)?;
+            }
+            None => return write_error(&mut writer, NOT_FOUND),
+        },
+        Source::Dir(ref dir) => match resolve_file(dir, &path) {
+            Some(file) => {
+                let data = fs::read(&file)?;
+                let mime_type = assets::mime_type(&file.to_string_lossy());
+                let headers = [("Content-Type", mime_type)];
+                write_response(&mut writer, OK, &headers, &data, head)?;
+            }
+            None => return write_error(&mut writer, NOT_FOUND),
+        },
+    };
+
+    writer.shutdown(Shutdown::Both)
+}
+
+/// Reads the request line and the relevant headers, returns `None` for
+/// malformed requests.
+///
+/// The size of the request head must be limited by the reader, an incomplete
+/// line is treated as malformed request.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Option<Request>> {
+    let mut line = String::new();
+
+    reader.read_line(&mut line)?;
+    if !line.ends_with('\n') {
+        return Ok(None);
+    }
+
+    let (method, target) = {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/1.") => {
+                (method.to_owned(), target.to_owned())
+            }
+            _ => return Ok(None),
+        }
+    };
+
+    if !target.starts_with('/') {
+        return Ok(None);
+    }
+
+    let target = target.split('#').next().unwrap_or("");
+    let (path, query) = match target.find('?') {
+        Some(idx) => (&target[..idx], Some(target[idx + 1..].to_owned())),
+        None => (target, None),
+    };
+
+    let mut cookie = None;
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        if !line.ends_with('\n') {
+            return Ok(None);
+        }
+
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some(idx) = header.find(':') {
+            if header[..idx].eq_ignore_ascii_case("cookie") {
+                cookie = Some(header[idx + 1..].trim().to_owned());
+            }
+        }
+    }
+
+    Ok(Some(Request {
+        method,
+        path: path.to_owned(),
+        query,
+        cookie,
+    }))
+}
+
+/// Finds the value of a `key=value` pair in a list separated by `sep`.
+fn find_pair<'a>(list: &'a str, sep: char, key: &str) -> Option<&'a str> {
+    list.split(sep).map(str::trim).find_map(|pair| {
+        let mut split = pair.splitn(2, '=');
+        match (split.next(), split.next()) {
+            (Some(k), Some(v)) if k == key => Some(v),
+            _ => None,
+        }
+    })
+}
+
+/// Resolves a decoded request path to a file within the directory.
+///
+/// Paths with parent components or escaping the directory through symbolic
+/// links are rejected, directories are resolved to their `index.html`.
+fn resolve_file(dir: &Path, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let mut file = dir.join(relative).canonicalize().ok()?;
+    if file.is_dir() {
+        file = file.join("index.html").canonicalize().ok()?;
+    }
+
+    if file.starts_with(dir) && file.is_file() {
+        Some(file)
+    } else {
+        None
+    }
+}
+
+fn write_response(
+    writer: &mut impl Write,
+    status: Status,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    head: bool,
+) -> io::Result<()> {
+    let mut response = format!("HTTP/1.1 {} {}\r\n", status.0, status.1);
+    for &(name, value) in headers {
+        write!(&mut response, "{}: {}\r\n", name, value).unwrap();
+    }
+    write!(
+        &mut response,
+        "Content-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    )
+    .unwrap();
+
+    writer.write_all(response.as_bytes())?;
+    if !head {
+        writer.write_all(body)?;
+    }
+    writer.flush()
+}
+
+fn write_error(writer: &mut impl Write, status: Status) -> io::Result<()> {
+    let headers = [("Content-Type", "text/plain; charset=utf-8")];
+    write_response(writer, status, &headers, status.1.as_bytes(), false)
+}
+
+/// Generates a random token of 32 hexadecimal digits.
+///
+/// The randomness is taken from the randomly seeded keys of `RandomState`.
+fn random_token() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or(0);
+
+    let mut token = String::with_capacity(32);
+    for round in 0..2u32 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(round);
+        hasher.write_u32(nanos);
+        write!(&mut token, "{:016x}", hasher.finish()).unwrap();
+    }
+
+    token
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use crate::server::*;
+
+    fn request(server: &AssetServer, target: &str, cookie: Option<&str>) -> String {
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        let mut request = format!("GET {} HTTP/1.1\r\nHost: {}\r\n", target, server.addr());
+        if let Some(cookie) = cookie {
+            request.push_str(&format!("Cookie: other=1; {}\r\n", cookie));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn token() {
+        let (a, b) = (random_token(), random_token());
+        assert_eq!(a.len(), 32);
+        assert!(a.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn serve_assets() {
+        let assets = Assets::new()
+            .add("index.html", &b"<h1>index</h1>"[..])
+            .add("app.js", &b"main()"[..]);
+        let mut server = AssetServer::serve_assets(assets).unwrap();
+        assert!(server.addr().ip().is_loopback());
+
+        let target = &server.url()[format!("http://{}", server.addr()).len()..];
+        let response = request(&server, target, None);
+        assert!(response.starts_with("HTTP/1.1 302 Found\r\n"));
+        assert!(response.contains("\r\nLocation: /\r\n"));
+
+        let cookie_start = response.find("Set-Cookie: ").unwrap() + "Set-Cookie: ".len();
+        let cookie = response[cookie_start..]
+            .split(';')
+            .next()
+            .unwrap()
+            .to_owned();
+
+        let response = request(&server, "/app.js", Some(&cookie));
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/javascript; charset=utf-8\r\n"));
+        assert!(response.ends_with("\r\n\r\nmain()"));
+
+        assert!(request(&server, "/", Some(&cookie)).ends_with("<h1>index</h1>"));
+        assert!(request(&server, "/missing.css", Some(&cookie)).starts_with("HTTP/1.1 404"));
+        assert!(request(&server, "/app.js", None).starts_with("HTTP/1.1 403"));
+        assert!(request(&server, "/app.js", Some("webview_rs_token=0")).starts_with("HTTP/1.1 403"));
+        assert!(request(&server, "/?webview_rs_token=0", None).starts_with("HTTP/1.1 403"));
+
+        server.shutdown();
+        assert!(TcpStream::connect(server.addr()).is_err());
+    }
+
+    #[test]
+    fn request_head_limit() {
+        let server = AssetServer::serve_assets(Assets::new()).unwrap();
+
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        stream.write_all(&[b'a'; MAX_HEAD_SIZE]).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        let head = format!("GET / HTTP/1.1\r\nX-Long: {:a<1$}", "", MAX_HEAD_SIZE - 24);
+        assert_eq!(head.len(), MAX_HEAD_SIZE);
+        stream.write_all(head.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+    }
+
+    #[test]
+    fn connection_limit() {
+        let server = AssetServer::serve_assets(Assets::new()).unwrap();
+        let idle: Vec<_> = (0..MAX_CONNECTIONS)
+            .map(|_| TcpStream::connect(server.addr()).unwrap())
+            .collect();
+
+        // the connection is closed without being handled
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        let mut response = Vec::new();
+        let _ = stream.read_to_end(&mut response);
+        assert!(response.is_empty());
+
+        drop(idle);
+    }
+
+    #[test]
+    fn resolve_files() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .canonicalize()
+            .unwrap();
+        let manifest = dir.join("Cargo.toml");
+
+        assert_eq!(resolve_file(&dir, "/Cargo.toml"), Some(manifest));
+        assert_eq!(resolve_file(&dir, "/../Cargo.toml"), None);
+        assert_eq!(resolve_file(&dir, "/src/../Cargo.toml"), None);
+        assert_eq!(resolve_file(&dir, "/missing.txt"), None);
+    }
+}
@@ -0,0 +1,352 @@
+//! Module for timers executed by the main loop.
+//!
+//! Timers are registered with the C library, which calls
+//! `callback::timer_handler` with the timer's ID on the main thread, so no
+//! additional threads are required. The closures themselves are owned by the
+//! `Timers` registry of the `Webview`.
+
+use std::collections::HashMap;
+use std::os::raw::c_uint;
+use std::time::Duration;
+
+use crate::error::WebviewError;
+use crate::ffi;
+use crate::{Webview, WebviewWrapper};
+use webview_sys as sys;
+
+/// Type alias for a boxed type-erased timer callback.
+///
+/// The callback receives the pointer to the webview struct the timer has been
+/// registered for.
+pub type TimerFnBox = Box<dyn FnMut(*mut sys::webview)>;
+
+/// Handle for cancelling a timer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle(u64);
+
+/// Registry of all active timers of a webview
+pub struct Timers {
+    entries: HashMap<u64, Entry>,
+    running: Vec<Running>,
+    next_id: u64,
+}
+
+struct Entry {
+    source: c_uint,
+    repeat: bool,
+    func:   TimerFnBox,
+}
+
+/// Timer currently being executed (timers may be nested, since evaluating JS
+/// iterates the main loop)
+struct Running {
+    id:        u64,
+    cancelled: bool,
+}
+
+impl Timers {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            running: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Registers a timer, which is either executed once or repeatedly.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `WebviewError::Unsupported` if timers are not supported on
+    /// the current platform.
+    #[inline]
+    pub unsafe fn add(
+        &mut self,
+        webview: *mut sys::webview,
+        interval: Duration,
+        repeat: bool,
+        func: TimerFnBox,
+    ) -> Result<TimerHandle, WebviewError> {
+        let id = self.next_id;
+        let source = ffi::webview_timer_add(webview, interval, id)?;
+
+        self.next_id += 1;
+        self.entries.insert(
+            id,
+            Entry {
+                source,
+                repeat,
+                func,
+            },
+        );
+        Ok(TimerHandle(id))
+    }
+
+    /// Cancels a timer, returns `false` if the timer no longer exists.
+    ///
+    /// Timers can also be cancelled during their own execution.
+    #[inline]
+    pub fn remove(&mut self, handle: TimerHandle) -> bool {
+        if let Some(entry) = self.entries.remove(&handle.0) {
+            unsafe { ffi::webview_timer_remove(entry.source) };
+            return true;
+        }
+
+        match self
+            .running
+            .iter_mut()
+            .find(|running| running.id == handle.0)
+        {
+            Some(ref mut running) if !running.cancelled => {
+                running.cancelled = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Cancels all timers.
+    #[inline]
+    pub fn clear(&mut self) {
+        for (_, entry) in self.entries.drain() {
+            unsafe { ffi::webview_timer_remove(entry.source) };
+        }
+
+        for running in &mut self.running {
+            running.cancelled = true;
+        }
+    }
+
+    /// Executes the timer with the given ID and returns whether the timer
+    /// continues.
+    ///
+    /// The timer is removed from the registry during its execution, since the
+    /// callback requires mutable access to the webview containing it.
+    #[inline]
+    pub unsafe fn fire(timers: *mut Timers, webview: *mut sys::webview, id: u64) -> bool {
+        let mut entry = match (*timers).entries.remove(&id) {
+            Some(entry) => entry,
+            None => return false,
+        };
+
+        (*timers).running.push(Running {
+            id,
+            cancelled: false,
+        });
+        (entry.func)(webview);
+
+        let idx = (*timers)
+            .running
+            .iter()
+            .rposition(|running| running.id == id)
+            .expect("running timer missing");
+        let running = (*timers).running.remove(idx);
+
+        if entry.repeat && !running.cancelled {
+            (*timers).entries.insert(id, entry);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Converts a one-shot closure over the webview into a type-erased timer.
+#[inline]
+pub fn webview_timeout<F>(func: F) -> TimerFnBox
+where
+    F: FnOnce(&mut Webview) + 'static,
+{
+    let mut func = Some(func);
+    Box::new(move |webview| {
+        if let Some(func) = func.take() {
+            unsafe { func(&mut *(webview as *mut Webview)) };
+        }
+    })
+}
+
+/// Converts a repeated closure over the webview into a type-erased timer.
+#[inline]
+pub fn webview_interval<F>(mut func: F) -> TimerFnBox
+where
+    F: FnMut(&mut Webview) + 'static,
+{
+    Box::new(move |webview| unsafe { func(&mut *(webview as *mut Webview)) })
+}
+
+/// Converts a one-shot closure over the webview and its userdata into a
+/// type-erased timer.
+///
+/// # Safety
+///
+/// The timer must only be registered for a `WebviewWrapper` with the same
+/// userdata type `T`.
+#[inline]
+pub unsafe fn wrapper_timeout<T, F>(func: F) -> TimerFnBox
+where
+    T: 'static,
+    F: FnOnce(&mut Webview, &mut T) + 'static,
+{
+    let mut func = Some(func);
+    Box::new(move |webview| {
+        if let Some(func) = func.take() {
+            let wrapper = webview as *mut WebviewWrapper<T>;
+            func(&mut (*wrapper).inner, &mut (*wrapper).ext.userdata);
+        }
+    })
+}
+
+/// Converts a repeated closure over the webview and its userdata into a
+/// type-erased timer.
+///
+/// # Safety
+///
+/// The timer must only be registered for a `WebviewWrapper` with the same
+/// userdata type `T`.
+#[inline]
+pub unsafe fn wrapper_interval<T, F>(mut func: F) -> TimerFnBox
+where
+    T: 'static,
+    F: FnMut(&mut Webview, &mut T) + 'static,
+{
+    Box::new(move |webview| {
+        let wrapper = webview as *mut WebviewWrapper<T>;
+        func(&mut (*wrapper).inner, &mut (*wrapper).ext.userdata);
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+    use std::ptr;
+    use std::rc::Rc;
+
+    use crate::timer::*;
+
+    /// Inserts a timer without registering it with the C library, so it must
+    /// not be removed before it is fired.
+    fn insert(timers: *mut Timers, repeat: bool, mut func: impl FnMut() + 'static) -> TimerHandle {
+        unsafe {
+            let id = (*timers).next_id;
+            (*timers).next_id += 1;
+            (*timers).entries.insert(
+                id,
+                Entry {
+                    source: 0,
+                    repeat,
+                    func: Box::new(move |_| func()),
+                },
+            );
+            TimerHandle(id)
+        }
+    }
+
+    fn fire(timers: *mut Timers, handle: TimerHandle) -> bool {
+        unsafe { Timers::fire(timers, ptr::null_mut(), handle.0) }
+    }
+
+    #[test]
+    fn timeout() {
+        let mut timers = Timers::new();
+        let timers: *mut Timers = &mut timers;
+        let count = Rc::new(Cell::new(0));
+
+        let fired = Rc::clone(&count);
+        let handle = insert(timers, false, move || fired.set(fired.get() + 1));
+
+        assert!(!fire(timers, handle));
+        assert!(!fire(timers, handle));
+        assert_eq!(count.get(), 1);
+        unsafe {
+            assert!((*timers).entries.is_empty());
+            assert!((*timers).running.is_empty());
+            assert!(!(*timers).remove(handle));
+        }
+    }
+
+    #[test]
+    fn interval() {
+        let mut timers = Timers::new();
+        let timers: *mut Timers = &mut timers;
+        let count = Rc::new(Cell::new(0));
+
+        let fired = Rc::clone(&count);
+        let handle = insert(timers, true, move || fired.set(fired.get() + 1));
+
+        assert!(fire(timers, handle));
+        assert!(fire(timers, handle));
+        assert_eq!(count.get(), 2);
+        unsafe {
+            assert!((*timers).entries.contains_key(&handle.0));
+            assert!((*timers).running.is_empty());
+        }
+    }
+
+    #[test]
+    fn remove_during_own_callback() {
+        let mut timers = Timers::new();
+        let timers: *mut Timers = &mut timers;
+        let removed = Rc::new(Cell::new(None));
+
+        let result = Rc::clone(&removed);
+        let handle = insert(timers, true, move || unsafe {
+            // the first timer has ID 1
+            let handle = TimerHandle(1);
+            result.set(Some(((*timers).remove(handle), (*timers).remove(handle))));
+        });
+
+        // the timer is not re-inserted once its callback returns
+        assert!(!fire(timers, handle));
+        assert_eq!(removed.get(), Some((true, false)));
+        unsafe {
+            assert!((*timers).entries.is_empty());
+            assert!((*timers).running.is_empty());
+        }
+    }
+
+    #[test]
+    fn nested() {
+        let mut timers = Timers::new();
+        let timers: *mut Timers = &mut timers;
+        let inner_fired = Rc::new(Cell::new(0));
+
+        let outer = TimerHandle(1);
+        let inner = TimerHandle(2);
+        insert(timers, true, move || {
+            // e.g. evaluating Javascript iterates the main loop
+            assert!(fire(timers, inner));
+            assert!(unsafe { (*timers).running.len() == 1 });
+        });
+
+        let fired = Rc::clone(&inner_fired);
+        insert(timers, true, move || unsafe {
+            fired.set(fired.get() + 1);
+            assert_eq!((*timers).running.len(), 2);
+            // cancels the outer timer, which is still running
+            assert!((*timers).remove(outer));
+        });
+
+        assert!(!fire(timers, outer));
+        assert_eq!(inner_fired.get(), 1);
+        unsafe {
+            assert!(!(*timers).entries.contains_key(&outer.0));
+            assert!((*timers).entries.contains_key(&inner.0));
+            assert!((*timers).running.is_empty());
+        }
+    }
+
+    #[test]
+    fn clear_during_callback() {
+        let mut timers = Timers::new();
+        let timers: *mut Timers = &mut timers;
+
+        let handle = insert(timers, true, move || unsafe { (*timers).clear() });
+
+        assert!(!fire(timers, handle));
+        unsafe {
+            assert!((*timers).entries.is_empty());
+            assert!(!(*timers).remove(handle));
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Module for user scripts and stylesheets.
+//!
+//! In contrast to `Webview::eval` and `Webview::inject_css`, which only apply
+//! to the current page, user content is injected into every page loaded
+//! afterwards and therefore persists across navigations and reloads.
+
+use std::os::raw::c_int;
+
+use webview_sys as sys;
+
+/// The point at which a user script is injected into a page
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InjectionTime {
+    /// Before any other content of the document is loaded
+    DocumentStart,
+    /// After the document has been parsed, before subresources are loaded
+    DocumentEnd,
+}
+
+impl InjectionTime {
+    #[inline]
+    pub(crate) fn as_raw(self) -> c_int {
+        match self {
+            InjectionTime::DocumentStart => sys::WEBVIEW_INJECT_DOCUMENT_START,
+            InjectionTime::DocumentEnd => sys::WEBVIEW_INJECT_DOCUMENT_END,
+        }
+    }
+}
+
+/// A script injected into every page
+///
+/// By default, scripts are injected at the start of the document into all
+/// frames.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserScript {
+    pub(crate) source:          String,
+    pub(crate) injection_time:  InjectionTime,
+    pub(crate) main_frame_only: bool,
+}
+
+impl UserScript {
+    #[inline]
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source:          source.into(),
+            injection_time:  InjectionTime::DocumentStart,
+            main_frame_only: false,
+        }
+    }
+
+    #[inline]
+    pub fn set_injection_time(mut self, injection_time: InjectionTime) -> Self {
+        self.injection_time = injection_time;
+        self
+    }
+
+    /// Restricts the script to the main frame, excluding any iframes.
+    #[inline]
+    pub fn set_main_frame_only(mut self, main_frame_only: bool) -> Self {
+        self.main_frame_only = main_frame_only;
+        self
+    }
+}
+
+/// A stylesheet injected into every page
+///
+/// By default, stylesheets are injected into all frames.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserStyle {
+    pub(crate) source:          String,
+    pub(crate) main_frame_only: bool,
+}
+
+impl UserStyle {
+    #[inline]
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source:          source.into(),
+            main_frame_only: false,
+        }
+    }
+
+    /// Restricts the stylesheet to the main frame, excluding any iframes.
+    #[inline]
+    pub fn set_main_frame_only(mut self, main_frame_only: bool) -> Self {
+        self.main_frame_only = main_frame_only;
+        self
+    }
+}
@@ -0,0 +1,250 @@
+//! Module for representing Javascript values on the Rust side.
+//!
+//! A `JsValue` is an arbitrarily nested tree of Javascript values, which can
+//! be passed as argument to `Webview::eval_fn` and is serialized into
+//! Javascript source code by the `EvalBuffer`.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+use crate::json::{self, ParseError};
+
+/// Possible JS argument types
+///
+/// Integers are stored as either signed or unsigned 64-bit integers, non-finite
+/// floating point numbers are serialized as `NaN`, `Infinity` or `-Infinity`.
+/// Object entries are serialized in the order in which they are stored.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsValue<'s> {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Str(Cow<'s, str>),
+    Array(Vec<JsValue<'s>>),
+    Object(Vec<(Cow<'s, str>, JsValue<'s>)>),
+}
+
+impl<'s> JsValue<'s> {
+    /// Parses a JSON document, strings without escape sequences are borrowed
+    /// from the input.
+    #[inline]
+    pub fn from_json(json: &'s str) -> Result<Self, ParseError> {
+        json::parse(json)
+    }
+
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(*self, JsValue::Null)
+    }
+
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            JsValue::Bool(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as `i64` if it is an integer within range.
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            JsValue::Int(val) => Some(val),
+            JsValue::UInt(val) if val <= i64::MAX as u64 => Some(val as i64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as `u64` if it is a non-negative integer.
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            JsValue::Int(val) if val >= 0 => Some(val as u64),
+            JsValue::UInt(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// Returns any number as (potentially lossy) `f64`.
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            JsValue::Int(val) => Some(val as f64),
+            JsValue::UInt(val) => Some(val as f64),
+            JsValue::Float(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            JsValue::Str(ref string) => Some(string),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_array(&self) -> Option<&[JsValue<'s>]> {
+        match *self {
+            JsValue::Array(ref values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the first object entry with the given key.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&JsValue<'s>> {
+        match *self {
+            JsValue::Object(ref entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Creates an object from an iterator over key-value pairs
+    #[inline]
+    pub fn object<K, V>(entries: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<Cow<'s, str>>,
+        V: Into<JsValue<'s>>,
+    {
+        JsValue::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+
+    /// Creates an array from an iterator over values
+    #[inline]
+    pub fn array<V>(values: impl IntoIterator<Item = V>) -> Self
+    where
+        V: Into<JsValue<'s>>,
+    {
+        JsValue::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<'s> From<()> for JsValue<'s> {
+    #[inline]
+    fn from(_: ()) -> Self {
+        JsValue::Null
+    }
+}
+
+impl<'s> From<bool> for JsValue<'s> {
+    #[inline]
+    fn from(val: bool) -> Self {
+        JsValue::Bool(val)
+    }
+}
+
+macro_rules! impl_from_int {
+    ($variant:ident, $target:ty; $($source:ty),*) => {
+        $(
+            impl<'s> From<$source> for JsValue<'s> {
+                #[inline]
+                fn from(val: $source) -> Self {
+                    JsValue::$variant(val as $target)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(Int, i64; i8, i16, i32, i64, isize);
+impl_from_int!(UInt, u64; u8, u16, u32, u64, usize);
+impl_from_int!(Float, f64; f32, f64);
+
+impl<'s> From<&'s str> for JsValue<'s> {
+    #[inline]
+    fn from(val: &'s str) -> Self {
+        JsValue::Str(Cow::Borrowed(val))
+    }
+}
+
+impl<'s> From<&'s String> for JsValue<'s> {
+    #[inline]
+    fn from(val: &'s String) -> Self {
+        JsValue::Str(Cow::Borrowed(val))
+    }
+}
+
+impl<'s> From<String> for JsValue<'s> {
+    #[inline]
+    fn from(val: String) -> Self {
+        JsValue::Str(Cow::Owned(val))
+    }
+}
+
+impl<'s> From<Cow<'s, str>> for JsValue<'s> {
+    #[inline]
+    fn from(val: Cow<'s, str>) -> Self {
+        JsValue::Str(val)
+    }
+}
+
+impl<'s> From<char> for JsValue<'s> {
+    #[inline]
+    fn from(val: char) -> Self {
+        JsValue::Str(Cow::Owned(val.to_string()))
+    }
+}
+
+impl<'s, V> From<Option<V>> for JsValue<'s>
+where
+    V: Into<JsValue<'s>>,
+{
+    /// `None` is converted to `null`
+    #[inline]
+    fn from(val: Option<V>) -> Self {
+        val.map_or(JsValue::Null, Into::into)
+    }
+}
+
+impl<'s, V> From<Vec<V>> for JsValue<'s>
+where
+    V: Into<JsValue<'s>>,
+{
+    #[inline]
+    fn from(val: Vec<V>) -> Self {
+        JsValue::array(val)
+    }
+}
+
+impl<'s, 'a, V> From<&'a [V]> for JsValue<'s>
+where
+    V: Into<JsValue<'s>> + Clone,
+{
+    #[inline]
+    fn from(val: &'a [V]) -> Self {
+        JsValue::array(val.iter().cloned())
+    }
+}
+
+impl<'s, K, V> From<BTreeMap<K, V>> for JsValue<'s>
+where
+    K: Into<Cow<'s, str>>,
+    V: Into<JsValue<'s>>,
+{
+    #[inline]
+    fn from(val: BTreeMap<K, V>) -> Self {
+        JsValue::object(val)
+    }
+}
+
+impl<'s, K, V, S> From<HashMap<K, V, S>> for JsValue<'s>
+where
+    K: Into<Cow<'s, str>>,
+    V: Into<JsValue<'s>>,
+    S: BuildHasher,
+{
+    #[inline]
+    fn from(val: HashMap<K, V, S>) -> Self {
+        JsValue::object(val)
+    }
+}
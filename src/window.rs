@@ -0,0 +1,34 @@
+//! Module for the state of the webview window.
+
+use webview_sys as sys;
+
+/// The state in which the webview window is shown once it has been built
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InitialState {
+    Normal,
+    Maximized,
+    Minimized,
+    /// The window is not shown until `Webview::show` is called
+    Hidden,
+}
+
+impl Default for InitialState {
+    #[inline]
+    fn default() -> Self {
+        InitialState::Normal
+    }
+}
+
+bitflags! {
+    /// The state of the webview window
+    ///
+    /// Window managers may not report all states, e.g. the minimized state
+    /// is not available on some Wayland compositors.
+    pub struct WindowState: i32 {
+        const Visible    = sys::WEBVIEW_WINDOW_VISIBLE;
+        const Maximized  = sys::WEBVIEW_WINDOW_MAXIMIZED;
+        const Minimized  = sys::WEBVIEW_WINDOW_MINIMIZED;
+        const Fullscreen = sys::WEBVIEW_WINDOW_FULLSCREEN;
+        const Focused    = sys::WEBVIEW_WINDOW_FOCUSED;
+    }
+}
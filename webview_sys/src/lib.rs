@@ -1,5 +1,5 @@
 use std::mem;
-use std::os::raw::{c_char, c_int, c_void};
+use std::os::raw::{c_char, c_int, c_uint, c_void};
 
 use private::webview_private;
 
@@ -37,6 +37,63 @@ pub type c_extern_callback_fn = extern "system" fn(*mut webview, *const c_char);
 #[allow(non_camel_case_types)]
 pub type c_webview_dispatch_fn = extern "system" fn(*mut webview, *mut c_void);
 
+#[allow(non_camel_case_types)]
+pub type c_webview_timer_fn = extern "system" fn(*mut webview, *mut c_void) -> c_int;
+
+#[allow(non_camel_case_types)]
+pub type c_webview_lifecycle_fn = extern "system" fn(*mut webview, c_int) -> c_int;
+
+/// Lifecycle event for the DOM of a page being ready
+pub const WEBVIEW_EVENT_READY: c_int = 0;
+/// Lifecycle event for a page having finished loading
+pub const WEBVIEW_EVENT_LOAD_FINISHED: c_int = 1;
+/// Lifecycle event for a request to close the window
+pub const WEBVIEW_EVENT_CLOSE_REQUESTED: c_int = 2;
+
+#[allow(non_camel_case_types)]
+pub type c_webview_policy_fn =
+    extern "system" fn(*mut webview, *const c_char, c_int, c_int) -> c_int;
+
+/// Navigation policy for allowing a navigation
+pub const WEBVIEW_POLICY_ALLOW: c_int = 0;
+/// Navigation policy for denying a navigation
+pub const WEBVIEW_POLICY_DENY: c_int = 1;
+/// Navigation policy for opening the URL with the default application
+pub const WEBVIEW_POLICY_OPEN_EXTERNALLY: c_int = 2;
+
+#[allow(non_camel_case_types)]
+pub type c_webview_scheme_fn =
+    extern "system" fn(*mut webview, *const c_char, *const c_char, *mut c_void);
+
+/// Injection of user scripts at the start of the document
+pub const WEBVIEW_INJECT_DOCUMENT_START: c_int = 0;
+/// Injection of user scripts at the end of the document
+pub const WEBVIEW_INJECT_DOCUMENT_END: c_int = 1;
+
+#[allow(non_camel_case_types)]
+pub type c_webview_window_state_fn = extern "system" fn(*mut webview, c_int);
+
+/// Window state flag for a visible window
+pub const WEBVIEW_WINDOW_VISIBLE: c_int = 1;
+/// Window state flag for a maximized window
+pub const WEBVIEW_WINDOW_MAXIMIZED: c_int = 2;
+/// Window state flag for a minimized window
+pub const WEBVIEW_WINDOW_MINIMIZED: c_int = 4;
+/// Window state flag for a fullscreen window
+pub const WEBVIEW_WINDOW_FULLSCREEN: c_int = 8;
+/// Window state flag for a focused window
+pub const WEBVIEW_WINDOW_FOCUSED: c_int = 16;
+
+#[allow(non_camel_case_types)]
+pub type c_webview_file_fn = extern "system" fn(*mut c_void, *const c_char);
+
+/// File dialog for selecting files to open
+pub const WEBVIEW_FILE_DIALOG_OPEN: c_int = 0;
+/// File dialog for selecting a directory
+pub const WEBVIEW_FILE_DIALOG_OPEN_DIRECTORY: c_int = 1;
+/// File dialog for selecting the path to save a file at
+pub const WEBVIEW_FILE_DIALOG_SAVE: c_int = 2;
+
 #[allow(non_camel_case_types)]
 #[repr(C)]
 pub struct webview {
@@ -70,6 +127,177 @@ extern "C" {
         external_invoke_cb: Option<c_extern_callback_fn>,
     );
     //pub fn struct_webview_set_userdata(webview: *mut webview, userdata: *mut c_void);
+
+    /// Adds a main loop timer (returns 0 if timers are not supported)
+    pub fn webview_timer_add(
+        webview: *mut webview,
+        interval: c_uint,
+        func: Option<c_webview_timer_fn>,
+        arg: *mut c_void,
+    ) -> c_uint;
+
+    /// Removes a main loop timer
+    pub fn webview_timer_remove(id: c_uint);
+
+    /// Schedules a callback on the main thread, returns the ID of the
+    /// scheduled source (0 if the dispatch can not be cancelled)
+    pub fn webview_dispatch_add(
+        webview: *mut webview,
+        func: Option<c_webview_dispatch_fn>,
+        arg: *mut c_void,
+    ) -> c_uint;
+
+    /// Cancels a scheduled callback, which has not yet been executed
+    pub fn webview_dispatch_remove(id: c_uint);
+
+    /// Connects the callback for lifecycle events (GTK only)
+    pub fn webview_lifecycle_connect(
+        webview: *mut webview,
+        func: Option<c_webview_lifecycle_fn>,
+    );
+
+    /// Connects the callback for navigation policy decisions (GTK only)
+    pub fn webview_policy_connect(webview: *mut webview, func: Option<c_webview_policy_fn>);
+
+    /// Registers a custom URI scheme for a webview (returns -1 if custom
+    /// schemes are not supported and -2 if the scheme is already registered)
+    pub fn webview_scheme_register(
+        webview: *mut webview,
+        scheme: *const c_char,
+        func: Option<c_webview_scheme_fn>,
+    ) -> c_int;
+
+    /// Removes all custom URI schemes registered for a webview
+    pub fn webview_scheme_unregister_all(webview: *mut webview);
+
+    /// Answers a custom scheme request, the data is copied
+    pub fn webview_scheme_finish(
+        request: *mut c_void,
+        data: *const c_void,
+        len: usize,
+        mime_type: *const c_char,
+    );
+
+    /// Answers a custom scheme request with an error
+    pub fn webview_scheme_fail(request: *mut c_void, message: *const c_char);
+
+    /// Navigates to a URL (returns -1 if navigation is not supported)
+    pub fn webview_navigate(webview: *mut webview, url: *const c_char) -> c_int;
+
+    /// Loads HTML markup, relative URLs are resolved against the (nullable) base URI
+    pub fn webview_load_html(
+        webview: *mut webview,
+        html: *const c_char,
+        base_uri: *const c_char,
+    ) -> c_int;
+
+    /// Reloads the current page
+    pub fn webview_reload(webview: *mut webview) -> c_int;
+
+    /// Navigates back in history (returns 0 if there is no previous page)
+    pub fn webview_go_back(webview: *mut webview) -> c_int;
+
+    /// Navigates forward in history (returns 0 if there is no next page)
+    pub fn webview_go_forward(webview: *mut webview) -> c_int;
+
+    /// Adds a user script (returns -1 if user content is not supported)
+    pub fn webview_user_script_add(
+        webview: *mut webview,
+        js: *const c_char,
+        injection_time: c_int,
+        main_frame_only: c_int,
+    ) -> c_int;
+
+    /// Adds a user stylesheet (returns -1 if user content is not supported)
+    pub fn webview_user_style_add(
+        webview: *mut webview,
+        css: *const c_char,
+        main_frame_only: c_int,
+    ) -> c_int;
+
+    /// Resizes the window (returns -1 if window geometry is not supported)
+    pub fn webview_window_set_size(webview: *mut webview, width: c_int, height: c_int) -> c_int;
+
+    /// Writes the current size of the window
+    pub fn webview_window_size(
+        webview: *mut webview,
+        width: *mut c_int,
+        height: *mut c_int,
+    ) -> c_int;
+
+    /// Moves the window
+    pub fn webview_window_set_position(webview: *mut webview, x: c_int, y: c_int) -> c_int;
+
+    /// Writes the current position of the window
+    pub fn webview_window_position(webview: *mut webview, x: *mut c_int, y: *mut c_int) -> c_int;
+
+    /// Sets the minimum window size (removed for non-positive sizes)
+    pub fn webview_window_set_min_size(webview: *mut webview, width: c_int, height: c_int) -> c_int;
+
+    /// Sets the maximum window size (removed for non-positive sizes)
+    pub fn webview_window_set_max_size(webview: *mut webview, width: c_int, height: c_int) -> c_int;
+
+    /// Centers the window on its current monitor
+    pub fn webview_window_center(webview: *mut webview) -> c_int;
+
+    /// Shows or hides the window decorations (returns -1 if not supported)
+    pub fn webview_window_set_decorated(webview: *mut webview, decorated: c_int) -> c_int;
+
+    /// Keeps the window above all other windows
+    pub fn webview_window_set_always_on_top(webview: *mut webview, always_on_top: c_int) -> c_int;
+
+    /// Makes the background of the window and the webview transparent
+    pub fn webview_window_set_transparent(webview: *mut webview, transparent: c_int) -> c_int;
+
+    /// Sets the window icon from encoded image data (returns -2 for invalid data)
+    pub fn webview_window_set_icon(webview: *mut webview, data: *const c_void, len: usize) -> c_int;
+
+    /// Shows or hides the window
+    pub fn webview_window_set_visible(webview: *mut webview, visible: c_int) -> c_int;
+
+    /// Maximizes or unmaximizes the window
+    pub fn webview_window_set_maximized(webview: *mut webview, maximized: c_int) -> c_int;
+
+    /// Minimizes or restores the window
+    pub fn webview_window_set_minimized(webview: *mut webview, minimized: c_int) -> c_int;
+
+    /// Returns the window state flags (returns -1 if not supported)
+    pub fn webview_window_state(webview: *mut webview) -> c_int;
+
+    /// Connects the callback for window state changes (GTK only)
+    pub fn webview_window_state_connect(
+        webview: *mut webview,
+        func: Option<c_webview_window_state_fn>,
+    );
+
+    /// Presents the window to the user and gives it the input focus
+    pub fn webview_window_focus(webview: *mut webview) -> c_int;
+
+    /// Shows a save dialog with a suggested file name
+    pub fn webview_dialog_save(
+        webview: *mut webview,
+        title: *const c_char,
+        name: *const c_char,
+        result: *mut c_char,
+        result_size: usize,
+    );
+
+    /// Shows a file dialog, the callback is called for each selected path
+    /// (returns 0 if cancelled and -1 if file dialogs are not supported)
+    pub fn webview_file_dialog(
+        webview: *mut webview,
+        action: c_int,
+        title: *const c_char,
+        directory: *const c_char,
+        name: *const c_char,
+        multiple: c_int,
+        confirm_overwrite: c_int,
+        filter_names: *const *const c_char,
+        filter_patterns: *const *const c_char,
+        filter_count: usize,
+        func: Option<c_webview_file_fn>,
+        arg: *mut c_void,
+    ) -> c_int;
 }
 
 extern "C" {
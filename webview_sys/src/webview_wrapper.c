@@ -4,6 +4,8 @@
 #include <stdalign.h>
 #include <stddef.h>
 
+typedef int (*webview_timer_fn)(struct webview *w, void *arg);
+
 size_t struct_webview_size() { return sizeof(struct webview); }
 
 size_t struct_webview_alignment() { return alignof(struct webview); }
@@ -39,4 +41,1142 @@ void struct_webview_set_debug(struct webview *webview, int debug) {
 void struct_webview_set_external_invoke_cb(
     struct webview *webview, webview_external_invoke_cb_t external_invoke_cb) {
   webview->external_invoke_cb = external_invoke_cb;
-}
\ No newline at end of file
+}
+
+/*
+ * Main loop timers
+ *
+ * The callback is executed on the main thread after each interval and the
+ * timer is removed once the callback returns 0. A return value of 0 for
+ * `webview_timer_add` indicates failure, since valid source IDs are positive.
+ */
+
+#if defined(WEBVIEW_GTK)
+struct webview_timer {
+  struct webview *w;
+  webview_timer_fn fn;
+  void *arg;
+};
+
+static gboolean webview_timer_cb(gpointer userdata) {
+  struct webview_timer *timer = (struct webview_timer *)userdata;
+  return timer->fn(timer->w, timer->arg) ? G_SOURCE_CONTINUE : G_SOURCE_REMOVE;
+}
+
+unsigned int webview_timer_add(struct webview *w, unsigned int interval,
+                               webview_timer_fn fn, void *arg) {
+  struct webview_timer *timer = g_new(struct webview_timer, 1);
+  timer->w = w;
+  timer->fn = fn;
+  timer->arg = arg;
+  return g_timeout_add_full(G_PRIORITY_DEFAULT, interval, webview_timer_cb,
+                            timer, g_free);
+}
+
+void webview_timer_remove(unsigned int id) { g_source_remove(id); }
+#else
+unsigned int webview_timer_add(struct webview *w, unsigned int interval,
+                               webview_timer_fn fn, void *arg) {
+  (void)w;
+  (void)interval;
+  (void)fn;
+  (void)arg;
+  return 0;
+}
+
+void webview_timer_remove(unsigned int id) { (void)id; }
+#endif
+
+/*
+ * Dispatch
+ *
+ * In contrast to `webview_dispatch`, the ID of the scheduled idle source is
+ * returned, so a pending dispatch can be cancelled before the webview is
+ * exited. A return value of 0 indicates that the dispatch can not be
+ * cancelled.
+ */
+
+#if defined(WEBVIEW_GTK)
+struct webview_dispatch_source {
+  struct webview *w;
+  webview_dispatch_fn fn;
+  void *arg;
+};
+
+static gboolean webview_dispatch_source_cb(gpointer userdata) {
+  struct webview_dispatch_source *source =
+      (struct webview_dispatch_source *)userdata;
+  source->fn(source->w, source->arg);
+  return G_SOURCE_REMOVE;
+}
+
+unsigned int webview_dispatch_add(struct webview *w, webview_dispatch_fn fn,
+                                  void *arg) {
+  struct webview_dispatch_source *source =
+      g_new(struct webview_dispatch_source, 1);
+  source->w = w;
+  source->fn = fn;
+  source->arg = arg;
+  return gdk_threads_add_idle_full(G_PRIORITY_DEFAULT_IDLE,
+                                   webview_dispatch_source_cb, source, g_free);
+}
+
+void webview_dispatch_remove(unsigned int id) { g_source_remove(id); }
+#else
+unsigned int webview_dispatch_add(struct webview *w, webview_dispatch_fn fn,
+                                  void *arg) {
+  webview_dispatch(w, fn, arg);
+  return 0;
+}
+
+void webview_dispatch_remove(unsigned int id) { (void)id; }
+#endif
+
+/*
+ * Lifecycle hooks
+ *
+ * The callback is executed on the main thread for each lifecycle event. For
+ * close requests, a return value of 0 vetoes closing the window.
+ */
+
+#define WEBVIEW_EVENT_READY 0
+#define WEBVIEW_EVENT_LOAD_FINISHED 1
+#define WEBVIEW_EVENT_CLOSE_REQUESTED 2
+
+typedef int (*webview_lifecycle_fn)(struct webview *w, int event);
+
+#if defined(WEBVIEW_GTK)
+#define WEBVIEW_LIFECYCLE_HANDLER "webview_rs_lifecycle"
+
+struct webview_lifecycle {
+  struct webview *w;
+  webview_lifecycle_fn fn;
+};
+
+static void webview_lifecycle_message_cb(WebKitUserContentManager *m,
+                                         WebKitJavascriptResult *r,
+                                         gpointer arg) {
+  (void)m;
+  (void)r;
+  struct webview_lifecycle *lifecycle = (struct webview_lifecycle *)arg;
+  lifecycle->fn(lifecycle->w, WEBVIEW_EVENT_READY);
+}
+
+static void webview_lifecycle_load_changed_cb(WebKitWebView *webview,
+                                              WebKitLoadEvent event,
+                                              gpointer arg) {
+  (void)webview;
+  struct webview_lifecycle *lifecycle = (struct webview_lifecycle *)arg;
+  if (event == WEBKIT_LOAD_FINISHED) {
+    lifecycle->fn(lifecycle->w, WEBVIEW_EVENT_LOAD_FINISHED);
+  }
+}
+
+static gboolean webview_lifecycle_delete_cb(GtkWidget *widget, GdkEvent *event,
+                                            gpointer arg) {
+  (void)widget;
+  (void)event;
+  struct webview_lifecycle *lifecycle = (struct webview_lifecycle *)arg;
+  return lifecycle->fn(lifecycle->w, WEBVIEW_EVENT_CLOSE_REQUESTED) ? FALSE
+                                                                    : TRUE;
+}
+
+void webview_lifecycle_connect(struct webview *w, webview_lifecycle_fn fn) {
+  WebKitWebView *webview = WEBKIT_WEB_VIEW(w->priv.webview);
+  WebKitUserContentManager *m =
+      webkit_web_view_get_user_content_manager(webview);
+  WebKitUserScript *script;
+
+  struct webview_lifecycle *lifecycle = g_new(struct webview_lifecycle, 1);
+  lifecycle->w = w;
+  lifecycle->fn = fn;
+  /* the hook data lives as long as the window */
+  g_object_set_data_full(G_OBJECT(w->priv.window), WEBVIEW_LIFECYCLE_HANDLER,
+                         lifecycle, g_free);
+
+  /* the library handles the script messages of all handlers as external
+   * invocations, so its handler is restricted to the `external` handler */
+  g_signal_handlers_disconnect_by_func(
+      m, G_CALLBACK(external_message_received_cb), w);
+  g_signal_connect(m, "script-message-received::external",
+                   G_CALLBACK(external_message_received_cb), w);
+
+  webkit_user_content_manager_register_script_message_handler(
+      m, WEBVIEW_LIFECYCLE_HANDLER);
+  g_signal_connect(m, "script-message-received::" WEBVIEW_LIFECYCLE_HANDLER,
+                   G_CALLBACK(webview_lifecycle_message_cb), lifecycle);
+
+  script = webkit_user_script_new(
+      "(function() {"
+      "  var ready = function() {"
+      "    window.webkit.messageHandlers." WEBVIEW_LIFECYCLE_HANDLER
+      ".postMessage('');"
+      "  };"
+      "  if (document.readyState === 'loading') {"
+      "    document.addEventListener('DOMContentLoaded', ready);"
+      "  } else {"
+      "    ready();"
+      "  }"
+      "})();",
+      WEBKIT_USER_CONTENT_INJECT_TOP_FRAME,
+      WEBKIT_USER_SCRIPT_INJECT_AT_DOCUMENT_START, NULL, NULL);
+  webkit_user_content_manager_add_script(m, script);
+  webkit_user_script_unref(script);
+
+  g_signal_connect(G_OBJECT(webview), "load-changed",
+                   G_CALLBACK(webview_lifecycle_load_changed_cb), lifecycle);
+  g_signal_connect(G_OBJECT(w->priv.window), "delete-event",
+                   G_CALLBACK(webview_lifecycle_delete_cb), lifecycle);
+}
+#else
+void webview_lifecycle_connect(struct webview *w, webview_lifecycle_fn fn) {
+  (void)w;
+  (void)fn;
+}
+#endif
+
+/*
+ * Runtime navigation
+ *
+ * The functions return 1 if a navigation has been started, 0 if there is no
+ * history entry to navigate to and -1 if navigation is not supported.
+ */
+
+#if defined(WEBVIEW_GTK)
+int webview_navigate(struct webview *w, const char *url) {
+  webkit_web_view_load_uri(WEBKIT_WEB_VIEW(w->priv.webview),
+                           webview_check_url(url));
+  return 1;
+}
+
+int webview_load_html(struct webview *w, const char *html,
+                      const char *base_uri) {
+  webkit_web_view_load_html(WEBKIT_WEB_VIEW(w->priv.webview), html, base_uri);
+  return 1;
+}
+
+int webview_reload(struct webview *w) {
+  webkit_web_view_reload(WEBKIT_WEB_VIEW(w->priv.webview));
+  return 1;
+}
+
+int webview_go_back(struct webview *w) {
+  WebKitWebView *webview = WEBKIT_WEB_VIEW(w->priv.webview);
+  if (!webkit_web_view_can_go_back(webview)) {
+    return 0;
+  }
+  webkit_web_view_go_back(webview);
+  return 1;
+}
+
+int webview_go_forward(struct webview *w) {
+  WebKitWebView *webview = WEBKIT_WEB_VIEW(w->priv.webview);
+  if (!webkit_web_view_can_go_forward(webview)) {
+    return 0;
+  }
+  webkit_web_view_go_forward(webview);
+  return 1;
+}
+#else
+int webview_navigate(struct webview *w, const char *url) {
+  (void)w;
+  (void)url;
+  return -1;
+}
+
+int webview_load_html(struct webview *w, const char *html,
+                      const char *base_uri) {
+  (void)w;
+  (void)html;
+  (void)base_uri;
+  return -1;
+}
+
+int webview_reload(struct webview *w) {
+  (void)w;
+  return -1;
+}
+
+int webview_go_back(struct webview *w) {
+  (void)w;
+  return -1;
+}
+
+int webview_go_forward(struct webview *w) {
+  (void)w;
+  return -1;
+}
+#endif
+
+/*
+ * Navigation policy
+ *
+ * The callback is executed for every navigation of the top frame and for
+ * requests to open new windows and returns one of the policies below.
+ * Allowed new window requests are loaded in the existing window, since the
+ * webview only has a single window.
+ */
+
+#define WEBVIEW_POLICY_ALLOW 0
+#define WEBVIEW_POLICY_DENY 1
+#define WEBVIEW_POLICY_OPEN_EXTERNALLY 2
+
+typedef int (*webview_policy_fn)(struct webview *w, const char *url,
+                                 int navigation_type, int new_window);
+
+#if defined(WEBVIEW_GTK)
+#define WEBVIEW_POLICY_DATA "webview_rs_policy"
+
+struct webview_policy {
+  struct webview *w;
+  webview_policy_fn fn;
+};
+
+static gboolean webview_policy_cb(WebKitWebView *webview,
+                                  WebKitPolicyDecision *decision,
+                                  WebKitPolicyDecisionType type,
+                                  gpointer arg) {
+  struct webview_policy *policy = (struct webview_policy *)arg;
+  WebKitNavigationAction *action;
+  WebKitURIRequest *request;
+  const char *uri;
+  int new_window;
+
+  switch (type) {
+  case WEBKIT_POLICY_DECISION_TYPE_NAVIGATION_ACTION:
+    new_window = 0;
+    break;
+  case WEBKIT_POLICY_DECISION_TYPE_NEW_WINDOW_ACTION:
+    new_window = 1;
+    break;
+  default:
+    return FALSE;
+  }
+
+  action = webkit_navigation_policy_decision_get_navigation_action(
+      WEBKIT_NAVIGATION_POLICY_DECISION(decision));
+  request = webkit_navigation_action_get_request(action);
+  uri = webkit_uri_request_get_uri(request);
+
+  switch (policy->fn(policy->w, uri,
+                     (int)webkit_navigation_action_get_navigation_type(action),
+                     new_window)) {
+  case WEBVIEW_POLICY_ALLOW:
+    if (new_window) {
+      webkit_web_view_load_request(webview, request);
+      webkit_policy_decision_ignore(decision);
+    } else {
+      webkit_policy_decision_use(decision);
+    }
+    break;
+  case WEBVIEW_POLICY_OPEN_EXTERNALLY:
+    gtk_show_uri_on_window(GTK_WINDOW(policy->w->priv.window), uri,
+                           GDK_CURRENT_TIME, NULL);
+    webkit_policy_decision_ignore(decision);
+    break;
+  default:
+    webkit_policy_decision_ignore(decision);
+    break;
+  }
+
+  return TRUE;
+}
+
+void webview_policy_connect(struct webview *w, webview_policy_fn fn) {
+  struct webview_policy *policy = g_new(struct webview_policy, 1);
+  policy->w = w;
+  policy->fn = fn;
+  /* the policy data lives as long as the window */
+  g_object_set_data_full(G_OBJECT(w->priv.window), WEBVIEW_POLICY_DATA, policy,
+                         g_free);
+
+  g_signal_connect(G_OBJECT(w->priv.webview), "decide-policy",
+                   G_CALLBACK(webview_policy_cb), policy);
+}
+#else
+void webview_policy_connect(struct webview *w, webview_policy_fn fn) {
+  (void)w;
+  (void)fn;
+}
+#endif
+
+/*
+ * Custom URI schemes
+ *
+ * The callback is executed for every request of a registered scheme and must
+ * answer the (opaque) request by calling either `webview_scheme_finish` or
+ * `webview_scheme_fail` before returning.
+ *
+ * Schemes can only be registered for the default web context, which is shared
+ * by all webviews of the process. Requests are therefore forwarded to the
+ * callback registered for the requesting webview and all registrations of a
+ * webview must be removed with `webview_scheme_unregister_all` before it is
+ * exited. `webview_scheme_register` returns 0 on success, -1 if custom schemes
+ * are not supported and -2 if the scheme is already registered for the
+ * webview.
+ */
+
+typedef void (*webview_scheme_fn)(struct webview *w, const char *scheme,
+                                  const char *path, void *request);
+
+#if defined(WEBVIEW_GTK)
+struct webview_scheme {
+  struct webview *w;
+  char *scheme;
+  webview_scheme_fn fn;
+};
+
+/* the registrations of all webviews and the schemes registered with the
+ * default web context, which can not be unregistered */
+static GSList *webview_schemes = NULL;
+static GHashTable *webview_context_schemes = NULL;
+
+void webview_scheme_fail(void *request, const char *message);
+
+static void webview_scheme_cb(WebKitURISchemeRequest *request, gpointer arg) {
+  const char *name = webkit_uri_scheme_request_get_scheme(request);
+  gpointer webview = webkit_uri_scheme_request_get_web_view(request);
+  GSList *item;
+  (void)arg;
+
+  for (item = webview_schemes; item != NULL; item = item->next) {
+    struct webview_scheme *scheme = (struct webview_scheme *)item->data;
+    if ((gpointer)scheme->w->priv.webview == webview &&
+        strcmp(scheme->scheme, name) == 0) {
+      scheme->fn(scheme->w, name, webkit_uri_scheme_request_get_path(request),
+                 request);
+      return;
+    }
+  }
+
+  webview_scheme_fail(request, "scheme not registered for this webview");
+}
+
+int webview_scheme_register(struct webview *w, const char *scheme,
+                            webview_scheme_fn fn) {
+  struct webview_scheme *data;
+  GSList *item;
+
+  for (item = webview_schemes; item != NULL; item = item->next) {
+    data = (struct webview_scheme *)item->data;
+    if (data->w == w && strcmp(data->scheme, scheme) == 0) {
+      return -2;
+    }
+  }
+
+  if (webview_context_schemes == NULL) {
+    webview_context_schemes =
+        g_hash_table_new_full(g_str_hash, g_str_equal, g_free, NULL);
+  }
+
+  if (!g_hash_table_contains(webview_context_schemes, scheme)) {
+    WebKitWebContext *context = webkit_web_context_get_default();
+    WebKitSecurityManager *security =
+        webkit_web_context_get_security_manager(context);
+
+    webkit_web_context_register_uri_scheme(context, scheme, webview_scheme_cb,
+                                           NULL, NULL);
+    webkit_security_manager_register_uri_scheme_as_secure(security, scheme);
+    webkit_security_manager_register_uri_scheme_as_cors_enabled(security,
+                                                                scheme);
+    g_hash_table_add(webview_context_schemes, g_strdup(scheme));
+  }
+
+  data = g_new(struct webview_scheme, 1);
+  data->w = w;
+  data->scheme = g_strdup(scheme);
+  data->fn = fn;
+  webview_schemes = g_slist_prepend(webview_schemes, data);
+  return 0;
+}
+
+void webview_scheme_unregister_all(struct webview *w) {
+  GSList *item = webview_schemes;
+
+  while (item != NULL) {
+    GSList *next = item->next;
+    struct webview_scheme *data = (struct webview_scheme *)item->data;
+    if (data->w == w) {
+      webview_schemes = g_slist_delete_link(webview_schemes, item);
+      g_free(data->scheme);
+      g_free(data);
+    }
+    item = next;
+  }
+}
+
+void webview_scheme_finish(void *request, const void *data, size_t len,
+                           const char *mime_type) {
+  GBytes *bytes = g_bytes_new(data, len);
+  GInputStream *stream = g_memory_input_stream_new_from_bytes(bytes);
+
+  webkit_uri_scheme_request_finish((WebKitURISchemeRequest *)request, stream,
+                                   (gint64)len, mime_type);
+  g_object_unref(stream);
+  g_bytes_unref(bytes);
+}
+
+void webview_scheme_fail(void *request, const char *message) {
+  GError *error =
+      g_error_new_literal(G_IO_ERROR, G_IO_ERROR_NOT_FOUND, message);
+  webkit_uri_scheme_request_finish_error((WebKitURISchemeRequest *)request,
+                                         error);
+  g_error_free(error);
+}
+#else
+int webview_scheme_register(struct webview *w, const char *scheme,
+                            webview_scheme_fn fn) {
+  (void)w;
+  (void)scheme;
+  (void)fn;
+  return -1;
+}
+
+void webview_scheme_unregister_all(struct webview *w) { (void)w; }
+
+void webview_scheme_finish(void *request, const void *data, size_t len,
+                           const char *mime_type) {
+  (void)request;
+  (void)data;
+  (void)len;
+  (void)mime_type;
+}
+
+void webview_scheme_fail(void *request, const char *message) {
+  (void)request;
+  (void)message;
+}
+#endif
+
+/*
+ * User content
+ *
+ * User scripts and stylesheets are injected into every page that is loaded
+ * after they have been added. The functions return 0 on success and -1 if
+ * user content is not supported.
+ */
+
+#define WEBVIEW_INJECT_DOCUMENT_START 0
+#define WEBVIEW_INJECT_DOCUMENT_END 1
+
+#if defined(WEBVIEW_GTK)
+static WebKitUserContentInjectedFrames
+webview_injected_frames(int main_frame_only) {
+  return main_frame_only ? WEBKIT_USER_CONTENT_INJECT_TOP_FRAME
+                         : WEBKIT_USER_CONTENT_INJECT_ALL_FRAMES;
+}
+
+int webview_user_script_add(struct webview *w, const char *js,
+                            int injection_time, int main_frame_only) {
+  WebKitUserContentManager *m = webkit_web_view_get_user_content_manager(
+      WEBKIT_WEB_VIEW(w->priv.webview));
+  WebKitUserScript *script = webkit_user_script_new(
+      js, webview_injected_frames(main_frame_only),
+      injection_time == WEBVIEW_INJECT_DOCUMENT_END
+          ? WEBKIT_USER_SCRIPT_INJECT_AT_DOCUMENT_END
+          : WEBKIT_USER_SCRIPT_INJECT_AT_DOCUMENT_START,
+      NULL, NULL);
+
+  webkit_user_content_manager_add_script(m, script);
+  webkit_user_script_unref(script);
+  return 0;
+}
+
+int webview_user_style_add(struct webview *w, const char *css,
+                           int main_frame_only) {
+  WebKitUserContentManager *m = webkit_web_view_get_user_content_manager(
+      WEBKIT_WEB_VIEW(w->priv.webview));
+  WebKitUserStyleSheet *style = webkit_user_style_sheet_new(
+      css, webview_injected_frames(main_frame_only),
+      WEBKIT_USER_STYLE_LEVEL_USER, NULL, NULL);
+
+  webkit_user_content_manager_add_style_sheet(m, style);
+  webkit_user_style_sheet_unref(style);
+  return 0;
+}
+#else
+int webview_user_script_add(struct webview *w, const char *js,
+                            int injection_time, int main_frame_only) {
+  (void)w;
+  (void)js;
+  (void)injection_time;
+  (void)main_frame_only;
+  return -1;
+}
+
+int webview_user_style_add(struct webview *w, const char *css,
+                           int main_frame_only) {
+  (void)w;
+  (void)css;
+  (void)main_frame_only;
+  return -1;
+}
+#endif
+
+/*
+ * Window geometry
+ *
+ * Sizes and positions are in logical pixels. The functions return 0 on
+ * success and -1 if the window geometry can not be controlled. Minimum and
+ * maximum sizes with a non-positive width or height are removed.
+ */
+
+#if defined(WEBVIEW_GTK)
+#define WEBVIEW_GEOMETRY_KEY "webview_rs_geometry"
+
+struct webview_geometry {
+  int min_width;
+  int min_height;
+  int max_width;
+  int max_height;
+};
+
+static struct webview_geometry *webview_geometry_get(struct webview *w) {
+  struct webview_geometry *geometry = (struct webview_geometry *)
+      g_object_get_data(G_OBJECT(w->priv.window), WEBVIEW_GEOMETRY_KEY);
+  if (geometry == NULL) {
+    geometry = g_new0(struct webview_geometry, 1);
+    g_object_set_data_full(G_OBJECT(w->priv.window), WEBVIEW_GEOMETRY_KEY,
+                           geometry, g_free);
+  }
+
+  return geometry;
+}
+
+/* geometry hints replace all previous hints, so both are always set */
+static void webview_geometry_apply(struct webview *w,
+                                   struct webview_geometry *geometry) {
+  GdkGeometry hints;
+  int mask = 0;
+
+  if (geometry->min_width > 0 && geometry->min_height > 0) {
+    hints.min_width = geometry->min_width;
+    hints.min_height = geometry->min_height;
+    mask |= GDK_HINT_MIN_SIZE;
+  }
+
+  if (geometry->max_width > 0 && geometry->max_height > 0) {
+    hints.max_width = geometry->max_width;
+    hints.max_height = geometry->max_height;
+    mask |= GDK_HINT_MAX_SIZE;
+  }
+
+  gtk_window_set_geometry_hints(GTK_WINDOW(w->priv.window), NULL, &hints,
+                                (GdkWindowHints)mask);
+}
+
+int webview_window_set_size(struct webview *w, int width, int height) {
+  gtk_window_resize(GTK_WINDOW(w->priv.window), width, height);
+  return 0;
+}
+
+int webview_window_size(struct webview *w, int *width, int *height) {
+  gtk_window_get_size(GTK_WINDOW(w->priv.window), width, height);
+  return 0;
+}
+
+int webview_window_set_position(struct webview *w, int x, int y) {
+  gtk_window_move(GTK_WINDOW(w->priv.window), x, y);
+  return 0;
+}
+
+int webview_window_position(struct webview *w, int *x, int *y) {
+  gtk_window_get_position(GTK_WINDOW(w->priv.window), x, y);
+  return 0;
+}
+
+int webview_window_set_min_size(struct webview *w, int width, int height) {
+  struct webview_geometry *geometry = webview_geometry_get(w);
+  geometry->min_width = width;
+  geometry->min_height = height;
+  webview_geometry_apply(w, geometry);
+  return 0;
+}
+
+int webview_window_set_max_size(struct webview *w, int width, int height) {
+  struct webview_geometry *geometry = webview_geometry_get(w);
+  geometry->max_width = width;
+  geometry->max_height = height;
+  webview_geometry_apply(w, geometry);
+  return 0;
+}
+
+int webview_window_center(struct webview *w) {
+  GtkWindow *window = GTK_WINDOW(w->priv.window);
+  GdkDisplay *display = gtk_widget_get_display(w->priv.window);
+  GdkWindow *gdk_window = gtk_widget_get_window(w->priv.window);
+  GdkMonitor *monitor;
+  GdkRectangle area;
+  int width, height;
+
+  if (gdk_window == NULL) {
+    /* the window has not been realized yet */
+    gtk_window_set_position(window, GTK_WIN_POS_CENTER);
+    return 0;
+  }
+
+  monitor = gdk_display_get_monitor_at_window(display, gdk_window);
+  gdk_monitor_get_workarea(monitor, &area);
+  gtk_window_get_size(window, &width, &height);
+  gtk_window_move(window, area.x + (area.width - width) / 2,
+                  area.y + (area.height - height) / 2);
+  return 0;
+}
+#else
+int webview_window_set_size(struct webview *w, int width, int height) {
+  (void)w;
+  (void)width;
+  (void)height;
+  return -1;
+}
+
+int webview_window_size(struct webview *w, int *width, int *height) {
+  (void)w;
+  (void)width;
+  (void)height;
+  return -1;
+}
+
+int webview_window_set_position(struct webview *w, int x, int y) {
+  (void)w;
+  (void)x;
+  (void)y;
+  return -1;
+}
+
+int webview_window_position(struct webview *w, int *x, int *y) {
+  (void)w;
+  (void)x;
+  (void)y;
+  return -1;
+}
+
+int webview_window_set_min_size(struct webview *w, int width, int height) {
+  (void)w;
+  (void)width;
+  (void)height;
+  return -1;
+}
+
+int webview_window_set_max_size(struct webview *w, int width, int height) {
+  (void)w;
+  (void)width;
+  (void)height;
+  return -1;
+}
+
+int webview_window_center(struct webview *w) {
+  (void)w;
+  return -1;
+}
+#endif
+
+/*
+ * Window options
+ *
+ * The functions return 0 on success and -1 if the option is not supported.
+ * Setting the icon returns -2 if the image data can not be decoded.
+ */
+
+#if defined(WEBVIEW_GTK)
+int webview_window_set_decorated(struct webview *w, int decorated) {
+  gtk_window_set_decorated(GTK_WINDOW(w->priv.window), decorated);
+  return 0;
+}
+
+int webview_window_set_always_on_top(struct webview *w, int always_on_top) {
+  gtk_window_set_keep_above(GTK_WINDOW(w->priv.window), always_on_top);
+  return 0;
+}
+
+int webview_window_set_transparent(struct webview *w, int transparent) {
+  GtkWidget *window = w->priv.window;
+  GdkScreen *screen = gtk_widget_get_screen(window);
+  GdkVisual *visual = transparent ? gdk_screen_get_rgba_visual(screen)
+                                  : gdk_screen_get_system_visual(screen);
+  GdkRGBA background = {1.0, 1.0, 1.0, transparent ? 0.0 : 1.0};
+  gboolean visible = gtk_widget_get_visible(window);
+
+  if (visual == NULL || (transparent && !gdk_screen_is_composited(screen))) {
+    return -1;
+  }
+
+  /* the visual can only be changed while the window is not realized */
+  if (gtk_widget_get_realized(window)) {
+    gtk_widget_hide(window);
+    gtk_widget_unrealize(window);
+  }
+
+  gtk_widget_set_visual(window, visual);
+  gtk_widget_set_app_paintable(window, transparent);
+  webkit_web_view_set_background_color(WEBKIT_WEB_VIEW(w->priv.webview),
+                                       &background);
+
+  if (visible) {
+    gtk_widget_show_all(window);
+  }
+
+  return 0;
+}
+
+int webview_window_set_icon(struct webview *w, const void *data, size_t len) {
+  GdkPixbufLoader *loader = gdk_pixbuf_loader_new();
+  GdkPixbuf *icon;
+  int result = -2;
+
+  if (gdk_pixbuf_loader_write(loader, (const guchar *)data, len, NULL) &&
+      gdk_pixbuf_loader_close(loader, NULL)) {
+    icon = gdk_pixbuf_loader_get_pixbuf(loader);
+    if (icon != NULL) {
+      gtk_window_set_icon(GTK_WINDOW(w->priv.window), icon);
+      result = 0;
+    }
+  } else {
+    gdk_pixbuf_loader_close(loader, NULL);
+  }
+
+  g_object_unref(loader);
+  return result;
+}
+
+int webview_window_set_visible(struct webview *w, int visible) {
+  if (visible) {
+    gtk_widget_show_all(w->priv.window);
+  } else {
+    gtk_widget_hide(w->priv.window);
+  }
+
+  return 0;
+}
+
+int webview_window_set_maximized(struct webview *w, int maximized) {
+  if (maximized) {
+    gtk_window_maximize(GTK_WINDOW(w->priv.window));
+  } else {
+    gtk_window_unmaximize(GTK_WINDOW(w->priv.window));
+  }
+
+  return 0;
+}
+
+int webview_window_set_minimized(struct webview *w, int minimized) {
+  if (minimized) {
+    gtk_window_iconify(GTK_WINDOW(w->priv.window));
+  } else {
+    gtk_window_deiconify(GTK_WINDOW(w->priv.window));
+  }
+
+  return 0;
+}
+#else
+int webview_window_set_decorated(struct webview *w, int decorated) {
+  (void)w;
+  (void)decorated;
+  return -1;
+}
+
+int webview_window_set_always_on_top(struct webview *w, int always_on_top) {
+  (void)w;
+  (void)always_on_top;
+  return -1;
+}
+
+int webview_window_set_transparent(struct webview *w, int transparent) {
+  (void)w;
+  (void)transparent;
+  return -1;
+}
+
+int webview_window_set_icon(struct webview *w, const void *data, size_t len) {
+  (void)w;
+  (void)data;
+  (void)len;
+  return -1;
+}
+
+int webview_window_set_visible(struct webview *w, int visible) {
+  (void)w;
+  (void)visible;
+  return -1;
+}
+
+int webview_window_set_maximized(struct webview *w, int maximized) {
+  (void)w;
+  (void)maximized;
+  return -1;
+}
+
+int webview_window_set_minimized(struct webview *w, int minimized) {
+  (void)w;
+  (void)minimized;
+  return -1;
+}
+#endif
+
+/*
+ * Window state
+ *
+ * The state of the window is a combination of the WEBVIEW_WINDOW_* flags.
+ * The callback is executed on the main thread whenever the state changes.
+ * `webview_window_state` returns -1 if the state can not be queried.
+ */
+
+#define WEBVIEW_WINDOW_VISIBLE 1
+#define WEBVIEW_WINDOW_MAXIMIZED 2
+#define WEBVIEW_WINDOW_MINIMIZED 4
+#define WEBVIEW_WINDOW_FULLSCREEN 8
+#define WEBVIEW_WINDOW_FOCUSED 16
+
+typedef void (*webview_window_state_fn)(struct webview *w, int state);
+
+#if defined(WEBVIEW_GTK)
+#define WEBVIEW_WINDOW_STATE_KEY "webview_rs_window_state"
+
+struct webview_window_state {
+  struct webview *w;
+  webview_window_state_fn fn;
+  int state;
+};
+
+static int webview_window_state_from(GtkWidget *window,
+                                     GdkWindowState gdk_state) {
+  int state = 0;
+  if (gtk_widget_get_visible(window)) {
+    state |= WEBVIEW_WINDOW_VISIBLE;
+  }
+  if (gdk_state & GDK_WINDOW_STATE_MAXIMIZED) {
+    state |= WEBVIEW_WINDOW_MAXIMIZED;
+  }
+  if (gdk_state & GDK_WINDOW_STATE_ICONIFIED) {
+    state |= WEBVIEW_WINDOW_MINIMIZED;
+  }
+  if (gdk_state & GDK_WINDOW_STATE_FULLSCREEN) {
+    state |= WEBVIEW_WINDOW_FULLSCREEN;
+  }
+  if (gdk_state & GDK_WINDOW_STATE_FOCUSED) {
+    state |= WEBVIEW_WINDOW_FOCUSED;
+  }
+
+  return state;
+}
+
+int webview_window_state(struct webview *w) {
+  GdkWindow *gdk_window = gtk_widget_get_window(w->priv.window);
+  GdkWindowState gdk_state =
+      gdk_window != NULL ? gdk_window_get_state(gdk_window) : 0;
+  return webview_window_state_from(w->priv.window, gdk_state);
+}
+
+static void webview_window_state_update(struct webview_window_state *ws,
+                                        int state) {
+  if (state != ws->state) {
+    ws->state = state;
+    ws->fn(ws->w, state);
+  }
+}
+
+static gboolean webview_window_state_event_cb(GtkWidget *widget,
+                                              GdkEventWindowState *event,
+                                              gpointer arg) {
+  struct webview_window_state *ws = (struct webview_window_state *)arg;
+  webview_window_state_update(
+      ws, webview_window_state_from(widget, event->new_window_state));
+  return FALSE;
+}
+
+static void webview_window_visibility_cb(GtkWidget *widget, gpointer arg) {
+  (void)widget;
+  struct webview_window_state *ws = (struct webview_window_state *)arg;
+  webview_window_state_update(ws, webview_window_state(ws->w));
+}
+
+void webview_window_state_connect(struct webview *w,
+                                  webview_window_state_fn fn) {
+  struct webview_window_state *ws = g_new(struct webview_window_state, 1);
+  ws->w = w;
+  ws->fn = fn;
+  ws->state = webview_window_state(w);
+  g_object_set_data_full(G_OBJECT(w->priv.window), WEBVIEW_WINDOW_STATE_KEY,
+                         ws, g_free);
+
+  g_signal_connect(G_OBJECT(w->priv.window), "window-state-event",
+                   G_CALLBACK(webview_window_state_event_cb), ws);
+  g_signal_connect_after(G_OBJECT(w->priv.window), "show",
+                         G_CALLBACK(webview_window_visibility_cb), ws);
+  g_signal_connect_after(G_OBJECT(w->priv.window), "hide",
+                         G_CALLBACK(webview_window_visibility_cb), ws);
+}
+
+int webview_window_focus(struct webview *w) {
+  gtk_window_present(GTK_WINDOW(w->priv.window));
+  return 0;
+}
+#else
+int webview_window_state(struct webview *w) {
+  (void)w;
+  return -1;
+}
+
+void webview_window_state_connect(struct webview *w,
+                                  webview_window_state_fn fn) {
+  (void)w;
+  (void)fn;
+}
+
+int webview_window_focus(struct webview *w) {
+  (void)w;
+  return -1;
+}
+#endif
+
+/*
+ * Save dialog
+ *
+ * Equivalent to the save dialog of `webview_dialog`, but with a suggested
+ * file name. The result buffer is left untouched if the dialog is cancelled.
+ */
+
+#if defined(WEBVIEW_GTK)
+void webview_dialog_save(struct webview *w, const char *title,
+                         const char *name, char *result, size_t resultsz) {
+  GtkWidget *dlg = gtk_file_chooser_dialog_new(
+      title, GTK_WINDOW(w->priv.window), GTK_FILE_CHOOSER_ACTION_SAVE,
+      "_Cancel", GTK_RESPONSE_CANCEL, "_Save", GTK_RESPONSE_ACCEPT, NULL);
+  gtk_file_chooser_set_local_only(GTK_FILE_CHOOSER(dlg), FALSE);
+  gtk_file_chooser_set_show_hidden(GTK_FILE_CHOOSER(dlg), TRUE);
+  gtk_file_chooser_set_do_overwrite_confirmation(GTK_FILE_CHOOSER(dlg), TRUE);
+  gtk_file_chooser_set_create_folders(GTK_FILE_CHOOSER(dlg), TRUE);
+  if (name != NULL && name[0] != '\0') {
+    gtk_file_chooser_set_current_name(GTK_FILE_CHOOSER(dlg), name);
+  }
+
+  if (gtk_dialog_run(GTK_DIALOG(dlg)) == GTK_RESPONSE_ACCEPT) {
+    gchar *filename = gtk_file_chooser_get_filename(GTK_FILE_CHOOSER(dlg));
+    if (filename != NULL) {
+      g_strlcpy(result, filename, resultsz);
+      g_free(filename);
+    }
+  }
+
+  gtk_widget_destroy(dlg);
+}
+#else
+void webview_dialog_save(struct webview *w, const char *title,
+                         const char *name, char *result, size_t resultsz) {
+  (void)name;
+  webview_dialog(w, WEBVIEW_DIALOG_TYPE_SAVE, 0, title, "", result, resultsz);
+}
+#endif
+
+/*
+ * File dialogs
+ *
+ * The callback is executed once for each selected path. Each filter consists
+ * of a name and a list of glob patterns separated by ';'. The directory and
+ * the suggested file name (only used for save dialogs) are nullable.
+ * Returns 1 if paths have been selected, 0 if the dialog has been cancelled
+ * and -1 if file dialogs are not supported.
+ */
+
+#define WEBVIEW_FILE_DIALOG_OPEN 0
+#define WEBVIEW_FILE_DIALOG_OPEN_DIRECTORY 1
+#define WEBVIEW_FILE_DIALOG_SAVE 2
+
+typedef void (*webview_file_fn)(void *arg, const char *path);
+
+#if defined(WEBVIEW_GTK)
+int webview_file_dialog(struct webview *w, int action, const char *title,
+                        const char *directory, const char *name, int multiple,
+                        int confirm_overwrite, const char *const *filter_names,
+                        const char *const *filter_patterns,
+                        size_t filter_count, webview_file_fn fn, void *arg) {
+  GtkFileChooserAction chooser_action = GTK_FILE_CHOOSER_ACTION_OPEN;
+  const char *accept = "_Open";
+  GtkWidget *dlg;
+  GtkFileChooser *chooser;
+  size_t i;
+  int result = 0;
+
+  if (action == WEBVIEW_FILE_DIALOG_OPEN_DIRECTORY) {
+    chooser_action = GTK_FILE_CHOOSER_ACTION_SELECT_FOLDER;
+  } else if (action == WEBVIEW_FILE_DIALOG_SAVE) {
+    chooser_action = GTK_FILE_CHOOSER_ACTION_SAVE;
+    accept = "_Save";
+  }
+
+  dlg = gtk_file_chooser_dialog_new(title, GTK_WINDOW(w->priv.window),
+                                    chooser_action, "_Cancel",
+                                    GTK_RESPONSE_CANCEL, accept,
+                                    GTK_RESPONSE_ACCEPT, NULL);
+  chooser = GTK_FILE_CHOOSER(dlg);
+  /* only local files have paths */
+  gtk_file_chooser_set_local_only(chooser, TRUE);
+  gtk_file_chooser_set_select_multiple(
+      chooser, multiple && action != WEBVIEW_FILE_DIALOG_SAVE);
+  gtk_file_chooser_set_create_folders(chooser,
+                                      action != WEBVIEW_FILE_DIALOG_OPEN);
+
+  if (action == WEBVIEW_FILE_DIALOG_SAVE) {
+    gtk_file_chooser_set_do_overwrite_confirmation(chooser, confirm_overwrite);
+    if (name != NULL) {
+      gtk_file_chooser_set_current_name(chooser, name);
+    }
+  }
+
+  if (directory != NULL) {
+    gtk_file_chooser_set_current_folder(chooser, directory);
+  }
+
+  for (i = 0; i < filter_count; i++) {
+    GtkFileFilter *filter = gtk_file_filter_new();
+    gchar **patterns = g_strsplit(filter_patterns[i], ";", -1);
+    gchar **pattern;
+
+    gtk_file_filter_set_name(filter, filter_names[i]);
+    for (pattern = patterns; *pattern != NULL; pattern++) {
+      if (**pattern != '\0') {
+        gtk_file_filter_add_pattern(filter, *pattern);
+      }
+    }
+
+    g_strfreev(patterns);
+    /* the chooser takes ownership of the floating filter */
+    gtk_file_chooser_add_filter(chooser, filter);
+  }
+
+  if (gtk_dialog_run(GTK_DIALOG(dlg)) == GTK_RESPONSE_ACCEPT) {
+    GSList *files = gtk_file_chooser_get_filenames(chooser);
+    GSList *file;
+
+    for (file = files; file != NULL; file = file->next) {
+      fn(arg, (const char *)file->data);
+    }
+
+    g_slist_free_full(files, g_free);
+    result = 1;
+  }
+
+  gtk_widget_destroy(dlg);
+  return result;
+}
+#else
+int webview_file_dialog(struct webview *w, int action, const char *title,
+                        const char *directory, const char *name, int multiple,
+                        int confirm_overwrite, const char *const *filter_names,
+                        const char *const *filter_patterns,
+                        size_t filter_count, webview_file_fn fn, void *arg) {
+  (void)w;
+  (void)action;
+  (void)title;
+  (void)directory;
+  (void)name;
+  (void)multiple;
+  (void)confirm_overwrite;
+  (void)filter_names;
+  (void)filter_patterns;
+  (void)filter_count;
+  (void)fn;
+  (void)arg;
+  return -1;
+}
+#endif